@@ -0,0 +1,60 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use criterion_cycles_per_byte::CyclesPerByte;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sundae::{aead::KeyInit, AeadInPlace, Nonce, SundaeAes, Tag};
+
+// Packets per batch
+pub const PACKETS: usize = 64;
+
+fn bench(c: &mut Criterion<CyclesPerByte>) {
+    let mut group = c.benchmark_group("batch");
+    let mut rng = OsRng;
+    let ad = [0u8; 16];
+    let mut key = [0u8; 16];
+    rng.fill_bytes(&mut key);
+    let cipher = SundaeAes::new(&key.into());
+
+    let nonces: Vec<[u8; 8]> = (0..PACKETS as u64).map(u64::to_be_bytes).collect();
+    let mut tags = vec![Tag::default(); PACKETS];
+
+    for size in &[16, 64, 256, 1024] {
+        let mut packets = vec![vec![0; *size]; PACKETS];
+        packets.iter_mut().for_each(|p| rng.fill_bytes(p));
+
+        group.throughput(Throughput::Bytes((size * PACKETS) as u64));
+
+        group.bench_function(BenchmarkId::new("one-by-one", size), |b| {
+            b.iter(|| {
+                for ((nonce, packet), tag) in nonces.iter().zip(&mut packets).zip(&mut tags) {
+                    *tag = cipher
+                        .encrypt_in_place_detached(nonce.into(), &ad, packet)
+                        .expect("Encryption error");
+                }
+            });
+        });
+
+        group.bench_function(BenchmarkId::new("batch", size), |b| {
+            b.iter(|| {
+                let mut messages: Vec<(&Nonce<_>, &[u8], &mut [u8])> = nonces
+                    .iter()
+                    .zip(&mut packets)
+                    .map(|(nonce, packet)| (nonce.into(), &ad[..], &mut packet[..]))
+                    .collect();
+                cipher
+                    .encrypt_batch_into(&mut messages, &mut tags)
+                    .expect("Encryption error")
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    name = benches;
+    config = Criterion::default().with_measurement(CyclesPerByte);
+    targets = bench
+);
+
+criterion_main!(benches);
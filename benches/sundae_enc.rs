@@ -3,10 +3,9 @@ use criterion_cycles_per_byte::CyclesPerByte;
 use rand::rngs::OsRng;
 use rand::RngCore;
 use sundae::{
-    aead::{Aead, KeyInit, consts::U8},
-    AeadInPlace, SundaeAes, Sundae,
+    aead::{Aead, KeyInit},
+    AeadInPlace, SundaeAes, SundaeGift64,
 };
-use gift_cipher::Gift128;
 
 pub const KB: usize = 1024;
 
@@ -18,8 +17,7 @@ fn bench(c: &mut Criterion<CyclesPerByte>) {
     let mut key = [0u8; 16];
     rng.fill_bytes(&mut key);
     let cipher = SundaeAes::new(&key.into());
-    let gift = Gift128::new(&key.into());
-    let cipher2: Sundae<Gift128, U8> = Sundae::from(gift);
+    let cipher2 = SundaeGift64::new(&key.into());
 
     for size in &[KB, 2 * KB, 4 * KB, 8 * KB, 16 * KB] {
         let mut m = vec![0; *size];
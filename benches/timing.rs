@@ -0,0 +1,227 @@
+//! dudect-style timing leakage test.
+//!
+//! Every case runs the same operation on two classes of inputs, one fixed and one random,
+//! interleaved in random order. Welch's t-test then compares the timing distributions, also after
+//! cropping the slowest measurements at several percentiles. |t| above 4.5 hints at a leak, the
+//! run fails if any case exceeds 10.
+//!
+//! Run with `cargo bench --bench timing --features gift`.
+
+use aes::Aes128;
+use rand::{rngs::ThreadRng, Rng, RngCore};
+use std::hint::black_box;
+use std::time::Instant;
+use sundae::{
+    aead::{
+        consts::{U0, U16, U8},
+        AeadInPlace, KeyInit,
+    },
+    gift::Gift128,
+    Sundae, SundaeNonceSize, Tag,
+};
+
+/// Measurements per case
+const SAMPLES: usize = 200_000;
+/// Measurements taken with the same set of prepared inputs
+const BATCH: usize = 10_000;
+/// Percentiles at which the slowest measurements are cropped
+const CROPS: [f64; 5] = [0.5, 0.75, 0.9, 0.95, 0.99];
+
+const LEAK_HINT: f64 = 4.5;
+const LEAK_FAIL: f64 = 10.0;
+
+/// Online mean and variance per class (Welford)
+#[derive(Default, Clone, Copy)]
+struct Welch {
+    n: [f64; 2],
+    mean: [f64; 2],
+    m2: [f64; 2],
+}
+
+impl Welch {
+    fn push(&mut self, class: usize, x: f64) {
+        self.n[class] += 1.0;
+        let delta = x - self.mean[class];
+        self.mean[class] += delta / self.n[class];
+        self.m2[class] += delta * (x - self.mean[class]);
+    }
+
+    fn t(&self) -> f64 {
+        if self.n[0] < 2.0 || self.n[1] < 2.0 {
+            return 0.0;
+        }
+        let var0 = self.m2[0] / (self.n[0] - 1.0);
+        let var1 = self.m2[1] / (self.n[1] - 1.0);
+        let se = (var0 / self.n[0] + var1 / self.n[1]).sqrt();
+        if se == 0.0 {
+            return 0.0;
+        }
+        (self.mean[0] - self.mean[1]) / se
+    }
+}
+
+/// Run `op` on inputs produced by `input(class, rng)` and return the largest |t| over all crops
+fn measure<I>(
+    mut input: impl FnMut(usize, &mut ThreadRng) -> I,
+    mut op: impl FnMut(&mut I),
+) -> f64 {
+    let mut rng = rand::thread_rng();
+    let mut thresholds = None;
+    let mut stats = [Welch::default(); CROPS.len() + 1];
+
+    let mut done = 0;
+    while done < SAMPLES {
+        let classes: Vec<usize> = (0..BATCH).map(|_| rng.gen_range(0..2)).collect();
+        let mut inputs: Vec<I> = classes.iter().map(|&c| input(c, &mut rng)).collect();
+
+        let mut timings = Vec::with_capacity(BATCH);
+        for i in inputs.iter_mut() {
+            let start = Instant::now();
+            op(black_box(i));
+            timings.push(start.elapsed().as_nanos() as f64);
+        }
+
+        // The first batch only calibrates the crop thresholds
+        let thresholds = match &thresholds {
+            Some(t) => t,
+            None => {
+                let mut sorted = timings.clone();
+                sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                thresholds = Some(CROPS.map(|p| sorted[(p * (BATCH - 1) as f64) as usize]));
+                continue;
+            }
+        };
+
+        for (&class, &t) in classes.iter().zip(timings.iter()) {
+            stats[0].push(class, t);
+            for (s, &limit) in stats[1..].iter_mut().zip(thresholds.iter()) {
+                if t <= limit {
+                    s.push(class, t);
+                }
+            }
+        }
+        done += BATCH;
+    }
+
+    stats.iter().map(|s| s.t().abs()).fold(0.0, f64::max)
+}
+
+fn random<const N: usize>(rng: &mut ThreadRng) -> [u8; N] {
+    let mut out = [0u8; N];
+    rng.fill_bytes(&mut out);
+    out
+}
+
+/// Tag verification, almost correct tags against random tags
+fn tag_verification<C: KeyInit + AeadInPlace<TagSize = U16>>(cipher: &C) -> f64 {
+    let nonce = Default::default();
+    let mut ct = *b"0123456789abcdef0123456789abcdef";
+    let mut tag = cipher
+        .encrypt_in_place_detached(&nonce, b"ad", &mut ct)
+        .unwrap();
+    tag[15] ^= 1;
+
+    measure(
+        |class, rng| {
+            let tag: Tag = if class == 0 { tag } else { random(rng).into() };
+            (ct, tag)
+        },
+        |(ct, tag)| {
+            let _ = black_box(cipher.decrypt_in_place_detached(&nonce, b"ad", ct, tag));
+        },
+    )
+}
+
+/// Encryption with a partial final block, fixed against random plaintexts
+fn partial_block<C: KeyInit + AeadInPlace>(cipher: &C) -> f64 {
+    let nonce = Default::default();
+    let fixed = [0u8; 23];
+
+    measure(
+        |class, rng| if class == 0 { fixed } else { random(rng) },
+        |msg| {
+            black_box(
+                cipher
+                    .encrypt_in_place_detached(&nonce, b"ad", msg)
+                    .unwrap(),
+            );
+        },
+    )
+}
+
+/// Empty associated data (b127 unset), fixed against random plaintexts
+fn empty_ad<C: KeyInit + AeadInPlace>(cipher: &C) -> f64 {
+    let nonce = Default::default();
+    let fixed = [0u8; 32];
+
+    measure(
+        |class, rng| if class == 0 { fixed } else { random(rng) },
+        |msg| {
+            black_box(cipher.encrypt_in_place_detached(&nonce, b"", msg).unwrap());
+        },
+    )
+}
+
+/// Empty message (b126 unset), fixed against random associated data
+fn empty_msg<C: KeyInit + AeadInPlace>(cipher: &C) -> f64 {
+    let nonce = Default::default();
+    let fixed = [0u8; 32];
+
+    measure(
+        |class, rng| if class == 0 { fixed } else { random(rng) },
+        |ad| {
+            black_box(
+                cipher
+                    .encrypt_in_place_detached(&nonce, ad, &mut [])
+                    .unwrap(),
+            );
+        },
+    )
+}
+
+fn cases<B, N>(name: &str, report: &mut impl FnMut(String, f64))
+where
+    B: KeyInit,
+    Sundae<B, U0>: KeyInit + AeadInPlace,
+    Sundae<B, N>: KeyInit + AeadInPlace<TagSize = U16>,
+    N: SundaeNonceSize,
+{
+    let key = random::<16>(&mut rand::thread_rng());
+    let cipher = Sundae::<B, N>::new_from_slice(&key).unwrap();
+    // Without a nonce the associated data can be empty
+    let nonceless = Sundae::<B, U0>::new_from_slice(&key).unwrap();
+
+    report(
+        format!("{} tag verification", name),
+        tag_verification(&cipher),
+    );
+    report(
+        format!("{} partial final block", name),
+        partial_block(&cipher),
+    );
+    report(format!("{} empty AD", name), empty_ad(&nonceless));
+    report(format!("{} empty message", name), empty_msg(&nonceless));
+}
+
+fn main() {
+    let mut leaks = 0;
+    let mut report = |case: String, t: f64| {
+        let verdict = if t > LEAK_FAIL {
+            leaks += 1;
+            "LEAK"
+        } else if t > LEAK_HINT {
+            "possible leak"
+        } else {
+            "ok"
+        };
+        println!("{:<36} max |t| = {:>7.2}  {}", case, t, verdict);
+    };
+
+    cases::<Aes128, U8>("SundaeAes", &mut report);
+    cases::<Gift128, U8>("SundaeGift64", &mut report);
+
+    if leaks > 0 {
+        eprintln!("{} case(s) exceed |t| = {}", leaks, LEAK_FAIL);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,148 @@
+//! Field arithmetic and block XOR used by the SUNDAE mode.
+//!
+//! Multiplication by 2 is the one from the SUNDAE-GIFT specification, it maps the bytes
+//! `(x0, ..., x15)` to `(x1, ..., x15, x0) ^ (0^10, x0, 0, x0, 0, x0, 0)`. That specification
+//! only covers 128-bit blocks. For 64-bit block ciphers this crate uses its own choice, the
+//! doubling in GF(2^64) modulo x^64 + x^4 + x^3 + x + 1 that CMAC uses, only ever computed with
+//! the plain integer implementation. It has not been checked against the definition for n = 64
+//! in the SUNDAE paper, so 64-bit ciphertexts may not match other SUNDAE implementations.
+//!
+//! On x86/x86_64 the SSSE3 multiplication is selected at runtime if the CPU supports it.
+//! Otherwise the `core::simd` implementation is used if the `simd` feature is enabled,
+//! and the plain integer implementation if not. XOR always uses the latter two: they inline
+//! into the callers and compile to a single SSE2 instruction on x86_64, while a
+//! `#[target_feature]` function is a call for every block. Multiplication only runs at the end
+//! of the associated data and of the message, so its call is paid at most twice per message.
+
+use crate::{Block, SundaeBlockSize};
+use cipher::consts::U16;
+
+#[cfg_attr(all(feature = "simd", not(test)), allow(dead_code))]
+mod portable;
+
+#[cfg(feature = "simd")]
+mod simd;
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod ssse3;
+
+#[cfg(not(feature = "simd"))]
+use portable as fallback;
+#[cfg(feature = "simd")]
+use simd as fallback;
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+cpufeatures::new!(ssse3_cpuid, "ssse3");
+
+/// Implementation selected by runtime CPU feature detection
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Backend {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    ssse3: bool,
+}
+
+impl Backend {
+    /// Detect the CPU features once, the result is kept by the cipher instance
+    pub(crate) fn detect() -> Self {
+        Self {
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            ssse3: ssse3_cpuid::get(),
+        }
+    }
+
+    /// Multiplication by 2 in the SUNDAE field of the block size
+    #[inline]
+    pub(crate) fn double<BlockSize: SundaeBlockSize>(
+        self,
+        block: &Block<BlockSize>,
+    ) -> Block<BlockSize> {
+        match BlockSize::USIZE {
+            8 => Block::clone_from_slice(&portable::double64(block.as_slice().try_into().unwrap())),
+            _ => Block::clone_from_slice(&self.double128(Block::<U16>::from_slice(block))),
+        }
+    }
+
+    /// XOR `other` into `block`
+    #[inline]
+    pub(crate) fn xor<BlockSize: SundaeBlockSize>(
+        self,
+        block: &mut Block<BlockSize>,
+        other: &Block<BlockSize>,
+    ) {
+        match BlockSize::USIZE {
+            16 => self.xor128(
+                Block::<U16>::from_mut_slice(block),
+                Block::<U16>::from_slice(other),
+            ),
+            _ => block
+                .iter_mut()
+                .zip(other.iter())
+                .for_each(|(a, b)| *a ^= b),
+        }
+    }
+
+    #[inline]
+    fn double128(self, block: &Block) -> Block {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        if self.ssse3 {
+            return unsafe { ssse3::double(block) };
+        }
+
+        fallback::double(block)
+    }
+
+    #[inline]
+    fn xor128(self, block: &mut Block, other: &Block) {
+        fallback::xor(block, other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::portable;
+    use crate::Block;
+
+    fn blocks() -> impl Iterator<Item = Block> {
+        (0u8..=255).map(|i| {
+            let mut block = Block::default();
+            for (j, b) in block.iter_mut().enumerate() {
+                *b = i
+                    .wrapping_mul(31)
+                    .wrapping_add(j as u8)
+                    .rotate_left(j as u32);
+            }
+            block
+        })
+    }
+
+    #[allow(dead_code)]
+    fn check(double: impl Fn(&Block) -> Block, xor: impl Fn(&mut Block, &Block)) {
+        for (a, b) in blocks().zip(blocks().skip(1)) {
+            assert_eq!(portable::double(&a), double(&a));
+
+            let (mut x, mut y) = (a, a);
+            portable::xor(&mut x, &b);
+            xor(&mut y, &b);
+            assert_eq!(x, y);
+        }
+    }
+
+    #[cfg(feature = "simd")]
+    #[test]
+    fn simd_agrees() {
+        check(super::simd::double, super::simd::xor);
+    }
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    #[test]
+    fn ssse3_agrees() {
+        if !super::ssse3_cpuid::get() {
+            return;
+        }
+
+        check(
+            |block| unsafe { super::ssse3::double(block) },
+            portable::xor,
+        );
+    }
+}
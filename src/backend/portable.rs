@@ -0,0 +1,36 @@
+//! Portable implementation in plain integer code.
+
+use crate::Block;
+
+/// Multiplication by 2 in the SUNDAE field
+#[inline]
+pub(crate) fn double(block: &Block) -> Block {
+    let mut out = *block;
+    // Rotate the bytes by one position towards the front
+    out.rotate_left(1);
+    // Reduction with the byte rotated out of the front
+    let top = block[0];
+    out[10] ^= top;
+    out[12] ^= top;
+    out[14] ^= top;
+    out
+}
+
+/// XOR `other` into `block`
+#[inline]
+pub(crate) fn xor(block: &mut Block, other: &Block) {
+    for (a, b) in block.iter_mut().zip(other.iter()) {
+        *a ^= b;
+    }
+}
+
+/// Multiplication by 2 in GF(2^64) modulo x^64 + x^4 + x^3 + x + 1
+///
+/// The polynomial is CMAC's for 64-bit blocks, not taken from the SUNDAE paper, see the module
+/// documentation of `backend`.
+#[inline]
+pub(crate) fn double64(block: &[u8; 8]) -> [u8; 8] {
+    let x = u64::from_be_bytes(*block);
+    // Reduction without branching on the bit shifted out
+    ((x << 1) ^ ((x >> 63) * 0b11011)).to_be_bytes()
+}
@@ -0,0 +1,27 @@
+//! Portable implementation on top of `core::simd`, requires a nightly compiler.
+
+use crate::Block;
+use core::simd::{simd_swizzle, u8x16};
+
+/// Multiplication by 2 in the SUNDAE field
+#[inline]
+pub(crate) fn double(block: &Block) -> Block {
+    let x = u8x16::from_array((*block).into());
+    let zero = u8x16::splat(0);
+    // Rotate the bytes by one position towards the front
+    let rotated = simd_swizzle!(x, [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 0]);
+    // Reduction with the byte rotated out of the front
+    let reduced = simd_swizzle!(
+        x,
+        zero,
+        [16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 0, 16, 0, 16, 0, 16]
+    );
+    (rotated ^ reduced).to_array().into()
+}
+
+/// XOR `other` into `block`
+#[inline]
+pub(crate) fn xor(block: &mut Block, other: &Block) {
+    let x = u8x16::from_array((*block).into()) ^ u8x16::from_array((*other).into());
+    *block = x.to_array().into();
+}
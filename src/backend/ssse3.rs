@@ -0,0 +1,29 @@
+//! SSSE3 implementation, callers must check for CPU support first.
+
+#[cfg(target_arch = "x86")]
+use core::arch::x86 as arch;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64 as arch;
+
+use crate::Block;
+use arch::{
+    __m128i, _mm_loadu_si128, _mm_set_epi8, _mm_shuffle_epi8, _mm_storeu_si128, _mm_xor_si128,
+};
+
+/// Multiplication by 2 in the SUNDAE field
+#[inline]
+#[target_feature(enable = "ssse3")]
+pub(crate) unsafe fn double(block: &Block) -> Block {
+    let block = _mm_loadu_si128(block.as_ptr() as *const __m128i);
+    // For Galois field multiplication, rotates the bytes by one position towards the front
+    let mul2 = _mm_set_epi8(0, 15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1);
+    // For Galois field multiplication, reduction with the byte rotated out of the front
+    let xor2 = _mm_set_epi8(-1, 0, -1, 0, -1, 0, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1);
+
+    let mut out = Block::default();
+    _mm_storeu_si128(
+        out.as_mut_ptr() as *mut __m128i,
+        _mm_xor_si128(_mm_shuffle_epi8(block, mul2), _mm_shuffle_epi8(block, xor2)),
+    );
+    out
+}
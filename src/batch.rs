@@ -0,0 +1,362 @@
+//! Encryption of many independent messages with interleaved block cipher calls.
+
+use crate::{nonce::domain, wipe, Block, Nonce, Sundae, SundaeNonceSize, Tag};
+
+use aead::Error;
+use cipher::{consts::U16, BlockCipher, BlockEncrypt, BlockSizeUser};
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Messages in flight at the same time, enough to fill the AES-NI pipeline
+const LANES: usize = 8;
+
+/// Message of a batch: nonce, associated data and the plaintext to encrypt in place
+pub type BatchMessage<'a, NonceSize> = (&'a Nonce<NonceSize>, &'a [u8], &'a mut [u8]);
+
+impl<B, NonceSize> Sundae<B, NonceSize>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+    NonceSize: SundaeNonceSize,
+{
+    /// Encrypt all `messages` in place and write their tags to `tags`, which has to be exactly
+    /// as long as `messages`
+    ///
+    /// The result is the same as encrypting the messages one by one, but up to 8 messages are
+    /// processed at a time: each step gathers the next block cipher input of every message in
+    /// flight into a single [`BlockEncrypt::encrypt_blocks`] call, which block ciphers with
+    /// parallel implementations like AES-NI pipeline. A finished message hands its slot to the
+    /// next one, so messages of different lengths keep all slots busy. This pays off for many
+    /// short messages, long ones gain nothing over calling
+    /// [`aead::AeadInPlace::encrypt_in_place_detached`].
+    ///
+    /// ```
+    /// # #[cfg(feature = "aes")]
+    /// # {
+    /// use sundae::{SundaeAes, Tag};
+    /// use sundae::aead::{AeadInPlace, KeyInit};
+    ///
+    /// let cipher = SundaeAes::new(b"just another key".into());
+    /// let (mut first, mut second) = (*b"first packet", *b"second packet");
+    ///
+    /// let mut tags = [Tag::default(); 2];
+    /// cipher
+    ///     .encrypt_batch_into(
+    ///         &mut [
+    ///             (b"nonce #1".into(), b"", &mut first[..]),
+    ///             (b"nonce #2".into(), b"", &mut second[..]),
+    ///         ],
+    ///         &mut tags,
+    ///     )
+    ///     .unwrap();
+    ///
+    /// cipher
+    ///     .decrypt_in_place_detached(b"nonce #2".into(), b"", &mut second, &tags[1])
+    ///     .expect("decryption failure!");
+    /// assert_eq!(&second, b"second packet");
+    /// # }
+    /// ```
+    pub fn encrypt_batch_into(
+        &self,
+        messages: &mut [BatchMessage<'_, NonceSize>],
+        tags: &mut [Tag],
+    ) -> Result<(), Error> {
+        if tags.len() != messages.len() {
+            return Err(Error);
+        }
+
+        let mut pending = messages.iter_mut().zip(tags.iter_mut());
+        let mut lanes: [Option<Lane<'_>>; LANES] = Default::default();
+        let mut blocks = [Block::default(); LANES];
+        // Lane of each gathered block
+        let mut owners = [0; LANES];
+
+        loop {
+            let mut count = 0;
+            for (i, slot) in lanes.iter_mut().enumerate() {
+                // Move on to the next message once a lane has nothing left to encrypt
+                let input = loop {
+                    if slot.is_none() {
+                        match pending.next() {
+                            Some(((nonce, ad, msg), tag)) => {
+                                *slot = Some(Lane::new(
+                                    domain::<NonceSize>(),
+                                    nonce.as_slice(),
+                                    ad,
+                                    msg,
+                                    tag,
+                                ))
+                            }
+                            None => break None,
+                        }
+                    }
+                    match slot.as_mut().and_then(|lane| lane.next_input(self)) {
+                        Some(input) => break Some(input),
+                        None => *slot = None,
+                    }
+                };
+
+                if let Some(input) = input {
+                    blocks[count] = input;
+                    owners[count] = i;
+                    count += 1;
+                }
+            }
+
+            if count == 0 {
+                break;
+            }
+
+            self.cipher.encrypt_blocks(&mut blocks[..count]);
+            for (block, &owner) in blocks.iter().zip(&owners).take(count) {
+                if let Some(lane) = &mut lanes[owner] {
+                    lane.output(block);
+                }
+            }
+        }
+
+        blocks.iter_mut().for_each(wipe);
+        Ok(())
+    }
+
+    /// Encrypt all `messages` in place and return their tags, see
+    /// [`Sundae::encrypt_batch_into`]
+    #[cfg(feature = "alloc")]
+    pub fn encrypt_batch(&self, messages: &mut [BatchMessage<'_, NonceSize>]) -> Vec<Tag> {
+        let mut tags = alloc::vec![Tag::default(); messages.len()];
+        // The lengths always match
+        self.encrypt_batch_into(messages, &mut tags)
+            .expect("batch encryption failure");
+        tags
+    }
+}
+
+// Where a lane is in the encryption of its message
+#[derive(Clone, Copy)]
+enum Step {
+    Initial,
+    AssociatedData,
+    Plaintext,
+    Keystream,
+}
+
+// What the output of the block cipher call in flight is used for
+#[derive(Clone, Copy)]
+enum Output {
+    // Next chaining value of the tag computation
+    Chain,
+    // The tag, and seed of the keystream
+    Tag,
+    // Keystream for `len` bytes of the message at `pos`
+    Keystream { pos: usize, len: usize },
+}
+
+// One message in flight, the same steps as sequential encryption with the block cipher calls
+// left to the caller
+struct Lane<'a> {
+    domain: u8,
+    nonce: &'a [u8],
+    ad: &'a [u8],
+    msg: &'a mut [u8],
+    tag: &'a mut Tag,
+    step: Step,
+    // Bytes of the current step already processed
+    pos: usize,
+    v: Block,
+    output: Output,
+}
+
+impl<'a> Lane<'a> {
+    fn new(domain: u8, nonce: &'a [u8], ad: &'a [u8], msg: &'a mut [u8], tag: &'a mut Tag) -> Self {
+        Self {
+            domain,
+            nonce,
+            ad,
+            msg,
+            tag,
+            step: Step::Initial,
+            pos: 0,
+            v: Block::default(),
+            output: Output::Chain,
+        }
+    }
+
+    // Next block cipher input, `None` once the message is encrypted
+    #[inline]
+    fn next_input<B, NonceSize>(&mut self, sundae: &Sundae<B, NonceSize>) -> Option<Block>
+    where
+        B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+        NonceSize: SundaeNonceSize,
+    {
+        // Full blocks in the middle of the message take the short way
+        match self.step {
+            Step::Plaintext if self.msg.len() > self.pos + 16 => {
+                let mut input = self.v;
+                sundae.backend.xor(
+                    &mut input,
+                    <Block>::from_slice(&self.msg[self.pos..self.pos + 16]),
+                );
+                self.pos += 16;
+                self.output = Output::Chain;
+                Some(input)
+            }
+            Step::Keystream if self.msg.len() >= self.pos + 16 => {
+                self.output = Output::Keystream {
+                    pos: self.pos,
+                    len: 16,
+                };
+                self.pos += 16;
+                Some(self.v)
+            }
+            _ => self.next_input_slow(sundae),
+        }
+    }
+
+    // Block cipher inputs at the edges of the message
+    fn next_input_slow<B, NonceSize>(&mut self, sundae: &Sundae<B, NonceSize>) -> Option<Block>
+    where
+        B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+        NonceSize: SundaeNonceSize,
+    {
+        let ad_len = self.nonce.len() + self.ad.len();
+        match self.step {
+            Step::Initial => {
+                // Setting the initial value for whether ad and pt are empty or not
+                let b127: u8 = if ad_len > 0 { 0b10000000 } else { 0 };
+                let b126: u8 = if self.msg.is_empty() { 0 } else { 0b01000000 };
+
+                let mut input = Block::default();
+                input[0] = b127 | b126 | self.domain;
+                self.step = if ad_len > 0 {
+                    Step::AssociatedData
+                } else {
+                    Step::Plaintext
+                };
+                self.output = if ad_len > 0 || !self.msg.is_empty() {
+                    Output::Chain
+                } else {
+                    Output::Tag
+                };
+                Some(input)
+            }
+            Step::AssociatedData => {
+                let mut block = Block::default();
+                let len = read_block(&[self.nonce, self.ad], self.pos, &mut block);
+                let input = self.chain(sundae, &mut block, len, ad_len, self.msg.is_empty());
+                // Finalizing the string starts over at its beginning
+                if self.pos == 0 {
+                    self.step = Step::Plaintext;
+                }
+                Some(input)
+            }
+            Step::Plaintext if !self.msg.is_empty() => {
+                let mut block = Block::default();
+                let len = read_block(&[&*self.msg], self.pos, &mut block);
+                let msg_len = self.msg.len();
+                Some(self.chain(sundae, &mut block, len, msg_len, true))
+            }
+            Step::Plaintext | Step::Keystream => {
+                if self.pos == self.msg.len() {
+                    wipe(&mut self.v);
+                    return None;
+                }
+                let len = (self.msg.len() - self.pos).min(16);
+                self.output = Output::Keystream { pos: self.pos, len };
+                self.pos += len;
+                Some(self.v)
+            }
+        }
+    }
+
+    // Next input of the CBC-MAC over a string of `total` bytes, `block` holds `len` bytes of it
+    // from `self.pos` on, the last block of the string is finalized
+    fn chain<B, NonceSize>(
+        &mut self,
+        sundae: &Sundae<B, NonceSize>,
+        block: &mut Block,
+        len: usize,
+        total: usize,
+        last_string: bool,
+    ) -> Block
+    where
+        B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+        NonceSize: SundaeNonceSize,
+    {
+        self.pos += len;
+        let mut input = self.v;
+
+        if self.pos < total {
+            sundae.backend.xor(&mut input, block);
+            self.output = Output::Chain;
+        } else {
+            // If remaining block is incomplete pad it
+            if len < 16 {
+                block[len] = 0x80;
+            }
+            sundae.backend.xor(&mut input, block);
+            input = if len < 16 {
+                sundae.backend.double(&input)
+            } else {
+                sundae.backend.double(&sundae.backend.double(&input))
+            };
+            self.pos = 0;
+            self.output = if last_string {
+                Output::Tag
+            } else {
+                Output::Chain
+            };
+        }
+
+        wipe(block);
+        input
+    }
+
+    // Take the output of the block cipher call for the last input
+    #[inline]
+    fn output(&mut self, block: &Block) {
+        self.v = *block;
+        match self.output {
+            Output::Chain => {}
+            Output::Tag => {
+                *self.tag = *block;
+                self.step = Step::Keystream;
+                self.pos = 0;
+            }
+            Output::Keystream { pos, len: 16 } => {
+                let chunk = <Block>::from_mut_slice(&mut self.msg[pos..pos + 16]);
+                for (b, k) in chunk.iter_mut().zip(block.iter()) {
+                    *b ^= k;
+                }
+            }
+            Output::Keystream { pos, len } => {
+                for (b, k) in self.msg[pos..pos + len].iter_mut().zip(block.iter()) {
+                    *b ^= k;
+                }
+            }
+        }
+    }
+}
+
+// Copy up to a block of the concatenated `parts` from `pos` on into `block`, returns the length
+fn read_block(parts: &[&[u8]], mut pos: usize, block: &mut Block) -> usize {
+    let mut len = 0;
+    for part in parts {
+        if pos >= part.len() {
+            pos -= part.len();
+            continue;
+        }
+        // Full block within a part
+        if len == 0 && part.len() - pos >= 16 {
+            block.copy_from_slice(&part[pos..pos + 16]);
+            return 16;
+        }
+        let n = (part.len() - pos).min(16 - len);
+        block[len..len + n].copy_from_slice(&part[pos..pos + n]);
+        len += n;
+        pos = 0;
+        if len == 16 {
+            break;
+        }
+    }
+    len
+}
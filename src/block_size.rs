@@ -0,0 +1,65 @@
+//! Block sizes SUNDAE is defined for.
+
+use crate::SundaeNonceSize;
+
+use cipher::{
+    consts::{U0, U16, U8},
+    generic_array::ArrayLength,
+};
+
+mod sealed {
+    pub trait Sealed {}
+
+    pub trait NonceFor<BlockSize> {}
+}
+
+/// Block sizes of the ciphers SUNDAE runs on: 64 and 128 bits
+///
+/// The tag is one block. Which nonce sizes a block size allows is given by [`SundaeNonceFor`].
+pub trait SundaeBlockSize: ArrayLength<u8> + sealed::Sealed {}
+
+impl sealed::Sealed for U8 {}
+impl sealed::Sealed for U16 {}
+
+impl SundaeBlockSize for U8 {}
+impl SundaeBlockSize for U16 {}
+
+/// Nonce sizes that fit a block of `BlockSize`
+///
+/// The nonce can not be longer than the block, so 128-bit block ciphers accept every
+/// [`SundaeNonceSize`] and 64-bit block ciphers only 0-bit and 64-bit nonces:
+///
+/// ```
+/// use cipher::{BlockEncrypt, BlockSizeUser};
+/// use sundae::{aead::consts::U8, Sundae};
+///
+/// fn sundae_64<B: BlockEncrypt + BlockSizeUser<BlockSize = U8>>(cipher: B) -> Sundae<B, U8> {
+///     cipher.into()
+/// }
+/// ```
+///
+/// Longer nonces do not type check:
+///
+/// ```compile_fail,E0277
+/// use cipher::{BlockEncrypt, BlockSizeUser};
+/// use sundae::{
+///     aead::consts::{U12, U8},
+///     Sundae,
+/// };
+///
+/// fn sundae_64<B: BlockEncrypt + BlockSizeUser<BlockSize = U8>>(cipher: B) -> Sundae<B, U12> {
+///     cipher.into()
+/// }
+/// ```
+pub trait SundaeNonceFor<BlockSize: SundaeBlockSize>:
+    SundaeNonceSize + sealed::NonceFor<BlockSize>
+{
+}
+
+impl<NonceSize: SundaeNonceSize> sealed::NonceFor<U16> for NonceSize {}
+impl sealed::NonceFor<U8> for U0 {}
+impl sealed::NonceFor<U8> for U8 {}
+
+impl<NonceSize: SundaeNonceSize> SundaeNonceFor<U16> for NonceSize {}
+impl SundaeNonceFor<U8> for U0 {}
+impl SundaeNonceFor<U8> for U8 {}
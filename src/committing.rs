@@ -0,0 +1,172 @@
+//! Key-committing SUNDAE.
+
+use crate::{nonce::domain, wipe, Block, Nonce, Sundae, SundaeNonceSize, Tag};
+
+use aead::{AeadCore, AeadInPlace, Error};
+use cipher::{
+    consts::{U0, U16, U32},
+    BlockCipher, BlockEncrypt, BlockSizeUser, Key, KeyInit, KeySizeUser,
+};
+use subtle::ConstantTimeEq;
+
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Bits b122 to b120 of the initial block, unused by SUNDAE itself, mark the commitment
+const COMMITMENT: u8 = 0b00000111;
+
+/// SUNDAE with a key commitment in front of the tag
+///
+/// Plain SUNDAE is not key-committing: knowing two keys, a ciphertext can be crafted that
+/// decrypts under both. This variant derives a commitment block from key and nonce, a CBC-MAC
+/// over the nonce with its own domain in the initial block, and prepends it to the 16-byte SUNDAE
+/// tag. Decryption rejects ciphertexts whose commitment does not match the key.
+///
+/// Finding two keys with the same commitment takes about 2^64 block cipher calls, the birthday
+/// bound of a single block. That assumes a block cipher that behaves like a random permutation:
+/// with a linear one, a nonce under which two given keys share the commitment is the solution of
+/// a linear system.
+///
+/// ```
+/// # #[cfg(all(feature = "aes", feature = "alloc"))]
+/// # {
+/// use sundae::{aes::Aes128, SundaeCommitting};
+/// use sundae::aead::{consts::U8, Aead, KeyInit};
+///
+/// let cipher = SundaeCommitting::<Aes128, U8>::new(b"just another key".into());
+/// let nonce = b"thenonce".into();
+///
+/// let ciphertext = cipher.encrypt(nonce, b"plaintext message".as_ref()).unwrap();
+/// assert_eq!(ciphertext.len(), 17 + 32);
+///
+/// let other = SundaeCommitting::<Aes128, U8>::new(b"another key, too".into());
+/// assert!(other.decrypt(nonce, ciphertext.as_ref()).is_err());
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct SundaeCommitting<B, NonceSize: SundaeNonceSize> {
+    sundae: Sundae<B, NonceSize>,
+}
+
+impl<B, NonceSize> KeySizeUser for SundaeCommitting<B, NonceSize>
+where
+    B: KeyInit,
+    NonceSize: SundaeNonceSize,
+{
+    type KeySize = B::KeySize;
+}
+
+impl<B, NonceSize> KeyInit for SundaeCommitting<B, NonceSize>
+where
+    B: BlockSizeUser<BlockSize = U16> + BlockEncrypt + KeyInit,
+    NonceSize: SundaeNonceSize,
+{
+    fn new(key: &Key<Self>) -> Self {
+        B::new(key).into()
+    }
+}
+
+impl<B, NonceSize> From<B> for SundaeCommitting<B, NonceSize>
+where
+    B: BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+    NonceSize: SundaeNonceSize,
+{
+    fn from(cipher: B) -> Self {
+        Self {
+            sundae: cipher.into(),
+        }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<B, NonceSize> Zeroize for SundaeCommitting<B, NonceSize>
+where
+    B: Zeroize,
+    NonceSize: SundaeNonceSize,
+{
+    fn zeroize(&mut self) {
+        self.sundae.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<B, NonceSize> ZeroizeOnDrop for SundaeCommitting<B, NonceSize>
+where
+    B: ZeroizeOnDrop,
+    NonceSize: SundaeNonceSize,
+{
+}
+
+impl<B, NonceSize> AeadCore for SundaeCommitting<B, NonceSize>
+where
+    NonceSize: SundaeNonceSize,
+{
+    type NonceSize = NonceSize;
+    // Commitment || SUNDAE tag
+    type TagSize = U32;
+    type CiphertextOverhead = U0;
+}
+
+impl<B, NonceSize> AeadInPlace for SundaeCommitting<B, NonceSize>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+    NonceSize: SundaeNonceSize,
+{
+    fn encrypt_in_place_detached(
+        &self,
+        nonce: &Nonce<NonceSize>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<aead::Tag<Self>, Error> {
+        let tag = self
+            .sundae
+            .encrypt_in_place_detached(nonce, associated_data, buffer)?;
+
+        let mut out = aead::Tag::<Self>::default();
+        out[..16].copy_from_slice(&self.commitment(nonce));
+        out[16..].copy_from_slice(&tag);
+        Ok(out)
+    }
+
+    fn decrypt_in_place_detached(
+        &self,
+        nonce: &Nonce<NonceSize>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+        tag: &aead::Tag<Self>,
+    ) -> Result<(), Error> {
+        let (commitment, tag) = tag.split_at(16);
+        let tag = Tag::from_slice(tag);
+
+        let mut expected = self.commitment(nonce);
+        let committed: bool = expected.ct_eq(commitment).into();
+        wipe(&mut expected);
+
+        // The tag is always checked, so a wrong commitment takes as long as a wrong tag
+        self.sundae
+            .decrypt_in_place_detached(nonce, associated_data, buffer, tag)?;
+
+        if committed {
+            Ok(())
+        } else {
+            // Never release plaintext for a key it was not committed to, restore the ciphertext
+            self.sundae.keystream(tag, buffer.into());
+            Err(Error)
+        }
+    }
+}
+
+impl<B, NonceSize> SundaeCommitting<B, NonceSize>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+    NonceSize: SundaeNonceSize,
+{
+    // Commitment block, CBC-MAC over the nonce starting from its own initial block
+    fn commitment(&self, nonce: &[u8]) -> Block {
+        let mut v = self
+            .sundae
+            .initial_block(domain::<NonceSize>() | COMMITMENT, true, false);
+        self.sundae.mac(&[nonce], &mut v);
+        v
+    }
+}
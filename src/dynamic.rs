@@ -0,0 +1,181 @@
+//! SUNDAE with the nonce length chosen at runtime.
+
+use crate::{nonce::nonce_domain, Sundae, Tag};
+
+use aead::Buffer;
+use cipher::{
+    consts::{U0, U16},
+    BlockCipher, BlockEncrypt, BlockSizeUser, Key, KeyInit, KeySizeUser,
+};
+use core::fmt;
+
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Errors of [`SundaeDyn`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DynError {
+    /// Nonce of the given length in bytes, SUNDAE only supports 0, 8, 12 and 16 bytes
+    NonceLength(usize),
+    /// Tag verification failed, or the buffer could not hold the tag
+    Aead,
+}
+
+impl fmt::Display for DynError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DynError::NonceLength(len) => {
+                write!(f, "unsupported SUNDAE nonce length: {} bytes", len)
+            }
+            DynError::Aead => f.write_str("authentication failed or buffer too small for the tag"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DynError {}
+
+impl From<DynError> for aead::Error {
+    fn from(_: DynError) -> Self {
+        aead::Error
+    }
+}
+
+impl From<aead::Error> for DynError {
+    fn from(_: aead::Error) -> Self {
+        DynError::Aead
+    }
+}
+
+/// SUNDAE taking nonces as slices, so the nonce length can be picked per message
+///
+/// One key schedule serves all four nonce lengths. Messages are compatible with
+/// `Sundae<B, NonceSize>` of the respective nonce size.
+///
+/// ```
+/// # #[cfg(feature = "aes")]
+/// # {
+/// use sundae::{aes::Aes128, DynError, SundaeDyn};
+/// use sundae::aead::KeyInit;
+///
+/// let cipher = SundaeDyn::<Aes128>::new(b"just another key".into());
+///
+/// let mut buffer = *b"plaintext message";
+/// let tag = cipher.encrypt_in_place_detached(b"96-bit nonce", b"", &mut buffer).unwrap();
+/// cipher.decrypt_in_place_detached(b"96-bit nonce", b"", &mut buffer, &tag).unwrap();
+/// assert_eq!(&buffer, b"plaintext message");
+///
+/// let result = cipher.encrypt_in_place_detached(b"nonce", b"", &mut buffer);
+/// assert_eq!(result, Err(DynError::NonceLength(5)));
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct SundaeDyn<B> {
+    sundae: Sundae<B, U0>,
+}
+
+impl<B> KeySizeUser for SundaeDyn<B>
+where
+    B: KeyInit,
+{
+    type KeySize = B::KeySize;
+}
+
+impl<B> KeyInit for SundaeDyn<B>
+where
+    B: BlockSizeUser<BlockSize = U16> + BlockEncrypt + KeyInit,
+{
+    fn new(key: &Key<Self>) -> Self {
+        B::new(key).into()
+    }
+}
+
+impl<B> From<B> for SundaeDyn<B>
+where
+    B: BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+{
+    fn from(cipher: B) -> Self {
+        Self {
+            sundae: cipher.into(),
+        }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<B> Zeroize for SundaeDyn<B>
+where
+    B: Zeroize,
+{
+    fn zeroize(&mut self) {
+        self.sundae.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<B> ZeroizeOnDrop for SundaeDyn<B> where B: ZeroizeOnDrop {}
+
+impl<B> SundaeDyn<B>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+{
+    /// Encrypt `buffer` in place and return the tag
+    pub fn encrypt_in_place_detached(
+        &self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<Tag, DynError> {
+        let domain = check_nonce(nonce)?;
+        Ok(self
+            .sundae
+            .encrypt_detached(domain, nonce, associated_data, buffer))
+    }
+
+    /// Decrypt `buffer` in place, on failure `buffer` keeps the ciphertext
+    pub fn decrypt_in_place_detached(
+        &self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        buffer: &mut [u8],
+        tag: &Tag,
+    ) -> Result<(), DynError> {
+        let domain = check_nonce(nonce)?;
+        Ok(self
+            .sundae
+            .decrypt_detached(domain, nonce, associated_data, buffer, tag)?)
+    }
+
+    /// Encrypt `buffer` in place and append the tag, like [`aead::AeadInPlace::encrypt_in_place`]
+    pub fn encrypt_in_place(
+        &self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        buffer: &mut dyn Buffer,
+    ) -> Result<(), DynError> {
+        let tag = self.encrypt_in_place_detached(nonce, associated_data, buffer.as_mut())?;
+        buffer.extend_from_slice(tag.as_slice())?;
+        Ok(())
+    }
+
+    /// Decrypt `buffer` in place and remove the appended tag, like
+    /// [`aead::AeadInPlace::decrypt_in_place`]
+    pub fn decrypt_in_place(
+        &self,
+        nonce: &[u8],
+        associated_data: &[u8],
+        buffer: &mut dyn Buffer,
+    ) -> Result<(), DynError> {
+        check_nonce(nonce)?;
+        let tag_pos = buffer.len().checked_sub(16).ok_or(DynError::Aead)?;
+
+        let (msg, tag) = buffer.as_mut().split_at_mut(tag_pos);
+        self.decrypt_in_place_detached(nonce, associated_data, msg, Tag::from_slice(tag))?;
+        buffer.truncate(tag_pos);
+        Ok(())
+    }
+}
+
+// Nonce length bits of the initial block
+fn check_nonce(nonce: &[u8]) -> Result<u8, DynError> {
+    nonce_domain(nonce.len()).map_err(|_| DynError::NonceLength(nonce.len()))
+}
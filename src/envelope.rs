@@ -0,0 +1,349 @@
+//! Self-describing envelopes for stored SUNDAE ciphertexts.
+//!
+//! An envelope records which cipher, nonce size and key a ciphertext was made with:
+//!
+//! ```text
+//! magic "SNDE" (4) || version (1) || cipher ID (1) || nonce length (1) || key ID length (1)
+//!     || key ID || nonce || ciphertext || tag (16)
+//! ```
+//!
+//! Everything up to and including the nonce is the header, which is the associated data of the
+//! encryption. Opening checks the header against the cipher before decrypting, so an envelope made
+//! with another block cipher or nonce size fails with a descriptive error rather than a failed
+//! tag. [`Envelope::parse`] reads the fields without copying, e.g. to pick the key by its ID.
+//!
+//! ```
+//! # #[cfg(all(feature = "aes", feature = "alloc"))]
+//! # {
+//! use sundae::{envelope, envelope::Envelope, SundaeAes};
+//! use sundae::aead::KeyInit;
+//!
+//! let cipher = SundaeAes::new(b"just another key".into());
+//! let sealed = envelope::seal(&cipher, b"key 1", b"thenonce".into(), b"plaintext message")
+//!     .unwrap();
+//!
+//! assert_eq!(Envelope::parse(&sealed).unwrap().key_id, b"key 1");
+//! let plaintext = envelope::open(&cipher, &sealed).expect("decryption failure!");
+//! assert_eq!(&plaintext, b"plaintext message");
+//! # }
+//! ```
+
+use crate::{Nonce, Sundae, SundaeNonceSize, Tag};
+
+use aead::AeadInPlace;
+use cipher::{consts::U16, BlockCipher, BlockEncrypt, BlockSizeUser};
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// First bytes of every envelope
+pub const MAGIC: [u8; 4] = *b"SNDE";
+
+/// Version of the format described in the [module documentation](self)
+pub const VERSION: u8 = 1;
+
+// Magic, version, cipher ID, nonce length and key ID length
+const FIXED_HEADER_SIZE: usize = 8;
+
+const TAG_SIZE: usize = 16;
+
+/// Block ciphers with an ID in the envelope header
+///
+/// IDs from 0x80 on are free for ciphers outside this crate. Those can not implement the trait
+/// for a block cipher of another crate, they pass the ID to the `*_with_id` functions instead.
+pub trait CipherId {
+    /// ID of the cipher in the envelope header
+    const CIPHER_ID: u8;
+}
+
+#[cfg(feature = "aes")]
+impl CipherId for aes::Aes128 {
+    const CIPHER_ID: u8 = 0x01;
+}
+
+#[cfg(feature = "gift")]
+impl CipherId for crate::gift::Gift128 {
+    const CIPHER_ID: u8 = 0x02;
+}
+
+/// Errors of sealing and opening envelopes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnvelopeError {
+    /// The input is too short for the lengths in its header
+    Truncated,
+    /// The input does not start with [`MAGIC`]
+    Magic,
+    /// Envelope of an unknown format version
+    Version(u8),
+    /// Envelope of the block cipher with the given ID
+    Cipher(u8),
+    /// Envelope with a nonce of the given length in bytes
+    NonceLength(u8),
+    /// Key ID of the given length in bytes, at most 255 bytes fit the header
+    KeyIdLength(usize),
+    /// The output buffer does not have the required length
+    BufferLength,
+    /// Tag verification failed
+    Aead,
+}
+
+impl fmt::Display for EnvelopeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvelopeError::Truncated => f.write_str("truncated envelope"),
+            EnvelopeError::Magic => f.write_str("not a SUNDAE envelope"),
+            EnvelopeError::Version(v) => write!(f, "unsupported envelope version: {}", v),
+            EnvelopeError::Cipher(id) => write!(f, "envelope of another cipher: ID {:#04x}", id),
+            EnvelopeError::NonceLength(len) => {
+                write!(f, "envelope with another nonce length: {} bytes", len)
+            }
+            EnvelopeError::KeyIdLength(len) => write!(f, "key ID too long: {} bytes", len),
+            EnvelopeError::BufferLength => f.write_str("output buffer of wrong length"),
+            EnvelopeError::Aead => f.write_str("authentication failed"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EnvelopeError {}
+
+impl From<aead::Error> for EnvelopeError {
+    fn from(_: aead::Error) -> Self {
+        EnvelopeError::Aead
+    }
+}
+
+/// Fields of an envelope, borrowed from its bytes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Envelope<'a> {
+    /// Header, the associated data of the encryption
+    pub header: &'a [u8],
+    /// ID of the block cipher, see [`CipherId`]
+    pub cipher_id: u8,
+    /// Key ID, opaque to SUNDAE
+    pub key_id: &'a [u8],
+    /// Nonce
+    pub nonce: &'a [u8],
+    /// Ciphertext
+    pub ciphertext: &'a [u8],
+    /// Tag
+    pub tag: &'a Tag,
+}
+
+impl<'a> Envelope<'a> {
+    /// Split `bytes` into the envelope fields, checking magic, version and lengths
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, EnvelopeError> {
+        let (header_len, ciphertext_len) = layout(bytes)?;
+        let (header, rest) = bytes.split_at(header_len);
+        let (ciphertext, tag) = rest.split_at(ciphertext_len);
+
+        let key_id_end = FIXED_HEADER_SIZE + usize::from(header[7]);
+        Ok(Self {
+            header,
+            cipher_id: header[5],
+            key_id: &header[FIXED_HEADER_SIZE..key_id_end],
+            nonce: &header[key_id_end..],
+            ciphertext,
+            tag: Tag::from_slice(tag),
+        })
+    }
+}
+
+/// Length of an envelope with a key ID and a plaintext of the given lengths
+pub fn sealed_len<NonceSize: SundaeNonceSize>(key_id_len: usize, msg_len: usize) -> usize {
+    FIXED_HEADER_SIZE + key_id_len + NonceSize::USIZE + msg_len + TAG_SIZE
+}
+
+/// Seal `plaintext` into `out`, which has to be exactly [`sealed_len`] bytes long
+pub fn seal_into<B, NonceSize>(
+    cipher: &Sundae<B, NonceSize>,
+    key_id: &[u8],
+    nonce: &Nonce<NonceSize>,
+    plaintext: &[u8],
+    out: &mut [u8],
+) -> Result<(), EnvelopeError>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt + CipherId,
+    NonceSize: SundaeNonceSize,
+{
+    seal_into_with_id(cipher, B::CIPHER_ID, key_id, nonce, plaintext, out)
+}
+
+/// [`seal_into`] with the cipher ID given by the caller, for block ciphers without [`CipherId`]
+pub fn seal_into_with_id<B, NonceSize>(
+    cipher: &Sundae<B, NonceSize>,
+    cipher_id: u8,
+    key_id: &[u8],
+    nonce: &Nonce<NonceSize>,
+    plaintext: &[u8],
+    out: &mut [u8],
+) -> Result<(), EnvelopeError>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+    NonceSize: SundaeNonceSize,
+{
+    let key_id_len =
+        u8::try_from(key_id.len()).map_err(|_| EnvelopeError::KeyIdLength(key_id.len()))?;
+    if out.len() != sealed_len::<NonceSize>(key_id.len(), plaintext.len()) {
+        return Err(EnvelopeError::BufferLength);
+    }
+
+    let header_len = FIXED_HEADER_SIZE + key_id.len() + nonce.len();
+    let (header, rest) = out.split_at_mut(header_len);
+    let (ciphertext, tag) = rest.split_at_mut(plaintext.len());
+
+    header[..4].copy_from_slice(&MAGIC);
+    header[4] = VERSION;
+    header[5] = cipher_id;
+    header[6] = nonce.len() as u8;
+    header[7] = key_id_len;
+    header[FIXED_HEADER_SIZE..][..key_id.len()].copy_from_slice(key_id);
+    header[FIXED_HEADER_SIZE + key_id.len()..].copy_from_slice(nonce);
+
+    ciphertext.copy_from_slice(plaintext);
+    let t = cipher.encrypt_in_place_detached(nonce, header, ciphertext)?;
+    tag.copy_from_slice(&t);
+
+    Ok(())
+}
+
+/// Open `envelope` in place and return the plaintext, a part of `envelope`
+///
+/// On failure `envelope` keeps the ciphertext.
+pub fn open_in_place<'a, B, NonceSize>(
+    cipher: &Sundae<B, NonceSize>,
+    envelope: &'a mut [u8],
+) -> Result<&'a mut [u8], EnvelopeError>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt + CipherId,
+    NonceSize: SundaeNonceSize,
+{
+    open_in_place_with_id(cipher, B::CIPHER_ID, envelope)
+}
+
+/// [`open_in_place`] with the cipher ID given by the caller, for block ciphers without
+/// [`CipherId`]
+pub fn open_in_place_with_id<'a, B, NonceSize>(
+    cipher: &Sundae<B, NonceSize>,
+    cipher_id: u8,
+    envelope: &'a mut [u8],
+) -> Result<&'a mut [u8], EnvelopeError>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+    NonceSize: SundaeNonceSize,
+{
+    let (header_len, ciphertext_len) = layout(envelope)?;
+    check::<NonceSize>(&Envelope::parse(envelope)?, cipher_id)?;
+
+    let (header, rest) = envelope.split_at_mut(header_len);
+    let (ciphertext, tag) = rest.split_at_mut(ciphertext_len);
+    let nonce = Nonce::<NonceSize>::from_slice(&header[header_len - NonceSize::USIZE..]);
+
+    cipher.decrypt_in_place_detached(nonce, header, ciphertext, Tag::from_slice(tag))?;
+    Ok(ciphertext)
+}
+
+/// Seal `plaintext` under `cipher` into a new envelope
+#[cfg(feature = "alloc")]
+pub fn seal<B, NonceSize>(
+    cipher: &Sundae<B, NonceSize>,
+    key_id: &[u8],
+    nonce: &Nonce<NonceSize>,
+    plaintext: &[u8],
+) -> Result<Vec<u8>, EnvelopeError>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt + CipherId,
+    NonceSize: SundaeNonceSize,
+{
+    seal_with_id(cipher, B::CIPHER_ID, key_id, nonce, plaintext)
+}
+
+/// [`seal`] with the cipher ID given by the caller, for block ciphers without [`CipherId`]
+#[cfg(feature = "alloc")]
+pub fn seal_with_id<B, NonceSize>(
+    cipher: &Sundae<B, NonceSize>,
+    cipher_id: u8,
+    key_id: &[u8],
+    nonce: &Nonce<NonceSize>,
+    plaintext: &[u8],
+) -> Result<Vec<u8>, EnvelopeError>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+    NonceSize: SundaeNonceSize,
+{
+    let mut out = alloc::vec![0; sealed_len::<NonceSize>(key_id.len(), plaintext.len())];
+    seal_into_with_id(cipher, cipher_id, key_id, nonce, plaintext, &mut out)?;
+    Ok(out)
+}
+
+/// Open `envelope` under `cipher` and return the plaintext
+#[cfg(feature = "alloc")]
+pub fn open<B, NonceSize>(
+    cipher: &Sundae<B, NonceSize>,
+    envelope: &[u8],
+) -> Result<Vec<u8>, EnvelopeError>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt + CipherId,
+    NonceSize: SundaeNonceSize,
+{
+    open_with_id(cipher, B::CIPHER_ID, envelope)
+}
+
+/// [`open`] with the cipher ID given by the caller, for block ciphers without [`CipherId`]
+#[cfg(feature = "alloc")]
+pub fn open_with_id<B, NonceSize>(
+    cipher: &Sundae<B, NonceSize>,
+    cipher_id: u8,
+    envelope: &[u8],
+) -> Result<Vec<u8>, EnvelopeError>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+    NonceSize: SundaeNonceSize,
+{
+    let envelope = Envelope::parse(envelope)?;
+    check::<NonceSize>(&envelope, cipher_id)?;
+
+    let mut out = envelope.ciphertext.to_vec();
+    cipher.decrypt_in_place_detached(
+        Nonce::<NonceSize>::from_slice(envelope.nonce),
+        envelope.header,
+        &mut out,
+        envelope.tag,
+    )?;
+    Ok(out)
+}
+
+// Check the header was made for the cipher ID and nonce size of the opening cipher
+fn check<NonceSize: SundaeNonceSize>(
+    envelope: &Envelope<'_>,
+    cipher_id: u8,
+) -> Result<(), EnvelopeError> {
+    if envelope.cipher_id != cipher_id {
+        return Err(EnvelopeError::Cipher(envelope.cipher_id));
+    }
+    if envelope.nonce.len() != NonceSize::USIZE {
+        return Err(EnvelopeError::NonceLength(envelope.nonce.len() as u8));
+    }
+    Ok(())
+}
+
+// Lengths of header and ciphertext, after checking magic, version and that the tag fits
+fn layout(bytes: &[u8]) -> Result<(usize, usize), EnvelopeError> {
+    if bytes.len() < FIXED_HEADER_SIZE {
+        return Err(EnvelopeError::Truncated);
+    }
+    if bytes[..4] != MAGIC {
+        return Err(EnvelopeError::Magic);
+    }
+    if bytes[4] != VERSION {
+        return Err(EnvelopeError::Version(bytes[4]));
+    }
+
+    let header_len = FIXED_HEADER_SIZE + usize::from(bytes[6]) + usize::from(bytes[7]);
+    let ciphertext_len = bytes
+        .len()
+        .checked_sub(header_len + TAG_SIZE)
+        .ok_or(EnvelopeError::Truncated)?;
+    Ok((header_len, ciphertext_len))
+}
@@ -0,0 +1,358 @@
+//! Incremental two-pass encryption and decryption for messages that do not fit in one buffer.
+
+use crate::{nonce::domain, verify, wipe, Block, Nonce, Sundae, SundaeNonceSize, Tag};
+
+use aead::Error;
+use cipher::{consts::U16, BlockCipher, BlockEncrypt, BlockSizeUser};
+
+/// First pass of incremental SUNDAE encryption, computing the tag
+///
+/// Associated data and plaintext can be supplied in chunks of any size, all associated data has to
+/// come before the plaintext. [`SundaeEncryptor::finalize`] returns the tag and a
+/// [`SundaeKeystream`] which encrypts the plaintext chunks in a second pass.
+///
+/// ```
+/// # #[cfg(feature = "aes")]
+/// # {
+/// use sundae::SundaeAes;
+/// use sundae::aead::KeyInit;
+///
+/// let cipher = SundaeAes::new(b"just another key".into());
+/// let nonce = b"thenonce".into();
+///
+/// let mut encryptor = cipher.encryptor(nonce);
+/// encryptor.update_ad(b"header").unwrap();
+/// encryptor.update_plaintext(b"first chunk, ");
+/// encryptor.update_plaintext(b"second chunk");
+/// let (tag, mut keystream) = encryptor.finalize();
+///
+/// let mut first = *b"first chunk, ";
+/// let mut second = *b"second chunk";
+/// keystream.apply_keystream(&mut first);
+/// keystream.apply_keystream(&mut second);
+///
+/// let mut decryptor = cipher.decryptor(nonce, &tag);
+/// decryptor.update_ad(b"header").unwrap();
+/// decryptor.decrypt(&mut first);
+/// decryptor.decrypt(&mut second);
+/// decryptor.verify().expect("decryption failure!");
+/// assert_eq!(&first, b"first chunk, ");
+/// # }
+/// ```
+pub struct SundaeEncryptor<'a, B, NonceSize>
+where
+    NonceSize: SundaeNonceSize,
+{
+    state: TagState<'a, B, NonceSize>,
+}
+
+/// Second pass of incremental SUNDAE encryption, and keystream of incremental decryption
+///
+/// Successive calls to [`SundaeKeystream::apply_keystream`] continue where the previous one stopped.
+pub struct SundaeKeystream<'a, B, NonceSize>
+where
+    NonceSize: SundaeNonceSize,
+{
+    sundae: &'a Sundae<B, NonceSize>,
+    v: Block,
+    // Bytes of `v` already used
+    pos: usize,
+}
+
+/// Incremental SUNDAE decryption
+///
+/// Ciphertext chunks are decrypted in place as they are supplied, the tag is only checked by
+/// [`SundaeDecryptor::verify`]. Plaintext must not be used before verification succeeded.
+pub struct SundaeDecryptor<'a, B, NonceSize>
+where
+    NonceSize: SundaeNonceSize,
+{
+    state: TagState<'a, B, NonceSize>,
+    keystream: SundaeKeystream<'a, B, NonceSize>,
+    tag: Tag,
+}
+
+impl<B, NonceSize> Sundae<B, NonceSize>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+    NonceSize: SundaeNonceSize,
+{
+    /// Start incremental encryption
+    pub fn encryptor<'a>(&'a self, nonce: &Nonce<NonceSize>) -> SundaeEncryptor<'a, B, NonceSize> {
+        SundaeEncryptor {
+            state: TagState::new(self, nonce),
+        }
+    }
+
+    /// Start incremental decryption
+    pub fn decryptor<'a>(
+        &'a self,
+        nonce: &Nonce<NonceSize>,
+        tag: &Tag,
+    ) -> SundaeDecryptor<'a, B, NonceSize> {
+        SundaeDecryptor {
+            state: TagState::new(self, nonce),
+            keystream: SundaeKeystream::new(self, tag),
+            tag: *tag,
+        }
+    }
+}
+
+impl<'a, B, NonceSize> SundaeEncryptor<'a, B, NonceSize>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+    NonceSize: SundaeNonceSize,
+{
+    /// Absorb associated data, fails once plaintext has been supplied
+    pub fn update_ad(&mut self, associated_data: &[u8]) -> Result<(), Error> {
+        self.state.update_ad(associated_data)
+    }
+
+    /// Absorb plaintext
+    pub fn update_plaintext(&mut self, msg: &[u8]) {
+        self.state.update_msg(msg);
+    }
+
+    /// Finish the first pass, returning the tag and the keystream for the second pass
+    pub fn finalize(mut self) -> (Tag, SundaeKeystream<'a, B, NonceSize>) {
+        let tag = self.state.finalize();
+        (tag, SundaeKeystream::new(self.state.sundae, &tag))
+    }
+}
+
+impl<'a, B, NonceSize> SundaeKeystream<'a, B, NonceSize>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+    NonceSize: SundaeNonceSize,
+{
+    pub(crate) fn new(sundae: &'a Sundae<B, NonceSize>, tag: &Tag) -> Self {
+        Self {
+            sundae,
+            v: *tag,
+            pos: 16,
+        }
+    }
+
+    /// XOR the next `buffer.len()` keystream bytes into `buffer`
+    pub fn apply_keystream(&mut self, buffer: &mut [u8]) {
+        // Rest of a partially used keystream block
+        let n = (16 - self.pos).min(buffer.len());
+        let (head, buffer) = buffer.split_at_mut(n);
+        for (b, k) in head.iter_mut().zip(&self.v[self.pos..]) {
+            *b ^= k;
+        }
+        self.pos += n;
+
+        let mut blocks = buffer.chunks_exact_mut(16);
+
+        // Procedure for complete blocks
+        for block in &mut blocks {
+            self.sundae.bc_encrypt(&mut self.v);
+            self.sundae
+                .backend
+                .xor(Block::from_mut_slice(block), &self.v);
+        }
+
+        // Procedure for last partial block
+        let rem = blocks.into_remainder();
+        if !rem.is_empty() {
+            self.sundae.bc_encrypt(&mut self.v);
+            for (b, k) in rem.iter_mut().zip(&self.v) {
+                *b ^= k;
+            }
+            self.pos = rem.len();
+        }
+    }
+}
+
+impl<'a, B, NonceSize> SundaeDecryptor<'a, B, NonceSize>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+    NonceSize: SundaeNonceSize,
+{
+    /// Absorb associated data, fails once ciphertext has been supplied
+    pub fn update_ad(&mut self, associated_data: &[u8]) -> Result<(), Error> {
+        self.state.update_ad(associated_data)
+    }
+
+    /// Decrypt a ciphertext chunk in place
+    pub fn decrypt(&mut self, buffer: &mut [u8]) {
+        self.keystream.apply_keystream(buffer);
+        self.state.update_msg(buffer);
+    }
+
+    /// Check the tag over all associated data and decrypted plaintext
+    pub fn verify(mut self) -> Result<(), Error> {
+        let expected = self.state.finalize();
+
+        // The plaintext chunks are already out, nothing to restore
+        verify(expected, &self.tag, || {})
+    }
+}
+
+// CBC-MAC chain, the last `len` bytes seen so far are held back in `buf`
+#[derive(Clone, Default)]
+struct Chain {
+    v: Block,
+    buf: Block,
+    len: usize,
+}
+
+impl Chain {
+    fn absorb<B, NonceSize>(&mut self, sundae: &Sundae<B, NonceSize>, data: &[u8])
+    where
+        B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+        NonceSize: SundaeNonceSize,
+    {
+        sundae.absorb(&mut self.v, &mut self.buf, &mut self.len, data);
+    }
+
+    // Absorb the held back last block, the chain can then continue with the next string
+    fn finish<B, NonceSize>(&mut self, sundae: &Sundae<B, NonceSize>)
+    where
+        B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+        NonceSize: SundaeNonceSize,
+    {
+        sundae.finish(&mut self.v, &mut self.buf, self.len);
+        self.len = 0;
+    }
+
+    fn wipe(&mut self) {
+        wipe(&mut self.v);
+        wipe(&mut self.buf);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    // Nothing absorbed yet
+    Start,
+    // Absorbing nonce || associated data
+    AssociatedData,
+    // Absorbing the message
+    Message,
+}
+
+// Tag computation over chunked input
+//
+// The initial block depends on whether the message is empty, which is only known once message
+// bytes arrive or the tag is finalized. Until then associated data is absorbed into a chain for
+// either case.
+struct TagState<'a, B, NonceSize>
+where
+    NonceSize: SundaeNonceSize,
+{
+    sundae: &'a Sundae<B, NonceSize>,
+    nonce: Nonce<NonceSize>,
+    domain: u8,
+    phase: Phase,
+    with_msg: Chain,
+    without_msg: Chain,
+}
+
+impl<'a, B, NonceSize> TagState<'a, B, NonceSize>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+    NonceSize: SundaeNonceSize,
+{
+    fn new(sundae: &'a Sundae<B, NonceSize>, nonce: &Nonce<NonceSize>) -> Self {
+        Self {
+            sundae,
+            nonce: nonce.clone(),
+            domain: domain::<NonceSize>(),
+            phase: Phase::Start,
+            with_msg: Chain::default(),
+            without_msg: Chain::default(),
+        }
+    }
+
+    // Non-empty associated data, the nonce is absorbed as its prefix
+    fn start_ad(&mut self) {
+        let sundae = self.sundae;
+        self.with_msg.v = sundae.initial_block(self.domain, true, true);
+        self.without_msg.v = sundae.initial_block(self.domain, true, false);
+        self.with_msg.absorb(sundae, &self.nonce);
+        self.without_msg.absorb(sundae, &self.nonce);
+        self.phase = Phase::AssociatedData;
+    }
+
+    fn update_ad(&mut self, associated_data: &[u8]) -> Result<(), Error> {
+        if self.phase == Phase::Message {
+            return Err(Error);
+        }
+        if associated_data.is_empty() {
+            return Ok(());
+        }
+
+        if self.phase == Phase::Start {
+            self.start_ad();
+        }
+        self.with_msg.absorb(self.sundae, associated_data);
+        self.without_msg.absorb(self.sundae, associated_data);
+
+        Ok(())
+    }
+
+    fn update_msg(&mut self, msg: &[u8]) {
+        if msg.is_empty() {
+            return;
+        }
+
+        if self.phase != Phase::Message {
+            if self.phase == Phase::Start && self.nonce.is_empty() {
+                self.with_msg.v = self.sundae.initial_block(self.domain, false, true);
+            } else {
+                if self.phase == Phase::Start {
+                    self.start_ad();
+                }
+                self.with_msg.finish(self.sundae);
+                self.without_msg.wipe();
+            }
+            self.phase = Phase::Message;
+        }
+
+        self.with_msg.absorb(self.sundae, msg);
+    }
+
+    fn finalize(&mut self) -> Tag {
+        if self.phase == Phase::Start && self.nonce.is_empty() {
+            return self.sundae.initial_block(self.domain, false, false);
+        }
+        if self.phase == Phase::Start {
+            self.start_ad();
+        }
+
+        let chain = match self.phase {
+            Phase::Message => &mut self.with_msg,
+            _ => &mut self.without_msg,
+        };
+        chain.finish(self.sundae);
+        let tag = chain.v;
+
+        self.with_msg.wipe();
+        self.without_msg.wipe();
+        tag
+    }
+}
+
+// Wipe the chains of the tag computation and the keystream block
+#[cfg(feature = "zeroize")]
+impl<'a, B, NonceSize> Drop for TagState<'a, B, NonceSize>
+where
+    NonceSize: SundaeNonceSize,
+{
+    fn drop(&mut self) {
+        self.with_msg.wipe();
+        self.without_msg.wipe();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<'a, B, NonceSize> Drop for SundaeKeystream<'a, B, NonceSize>
+where
+    NonceSize: SundaeNonceSize,
+{
+    fn drop(&mut self) {
+        wipe(&mut self.v);
+    }
+}
@@ -0,0 +1,121 @@
+//! Deterministic key wrapping with nonce-less SUNDAE.
+//!
+//! A key is wrapped under a key-encryption key (KEK) and an optional header, which is
+//! authenticated but not part of the output. The wrapped key is
+//!
+//! ```text
+//! tag (16 bytes) || ciphertext (key length)
+//! ```
+//!
+//! where tag and ciphertext are the output of `Sundae<B, U0>` with the header as associated data
+//! and the key as plaintext, the layout SUNDAE-GIFT uses for its ciphertexts. Wrapping is
+//! deterministic: the same key and header under the same KEK always give the same output, which
+//! is safe because the wrapped keys are uniformly random and not under attacker control.
+//!
+//! ```
+//! # #[cfg(all(feature = "aes", feature = "alloc"))]
+//! # {
+//! use sundae::{aes::Aes128, keywrap, Sundae};
+//! use sundae::aead::{consts::U0, KeyInit};
+//!
+//! let kek: Sundae<Aes128, U0> = Sundae::new(b"key encrypt key!".into());
+//! let key = b"0123456789abcdef0123456789abcdef";
+//!
+//! let wrapped = keywrap::wrap_key(&kek, key, b"device 42");
+//! assert_eq!(wrapped.len(), keywrap::wrapped_len(key.len()));
+//!
+//! let unwrapped = keywrap::unwrap_key(&kek, &wrapped, b"device 42").expect("unwrap failure!");
+//! assert_eq!(&unwrapped, key);
+//! # }
+//! ```
+
+use crate::{Sundae, Tag};
+
+use aead::{AeadInPlace, Error};
+use cipher::{
+    consts::{U0, U16},
+    BlockCipher, BlockEncrypt, BlockSizeUser,
+};
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Size of the tag in front of the wrapped key
+pub const TAG_SIZE: usize = 16;
+
+/// Length of a wrapped key of `key_len` bytes
+pub const fn wrapped_len(key_len: usize) -> usize {
+    key_len + TAG_SIZE
+}
+
+/// Wrap `key` into `out`, which has to be exactly [`wrapped_len`] bytes long
+pub fn wrap_key_into<B>(
+    kek: &Sundae<B, U0>,
+    key: &[u8],
+    header: &[u8],
+    out: &mut [u8],
+) -> Result<(), Error>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+{
+    if out.len() != wrapped_len(key.len()) {
+        return Err(Error);
+    }
+
+    let (tag, ciphertext) = out.split_at_mut(TAG_SIZE);
+    ciphertext.copy_from_slice(key);
+    let t = kek.encrypt_in_place_detached(&Default::default(), header, ciphertext)?;
+    tag.copy_from_slice(&t);
+
+    Ok(())
+}
+
+/// Unwrap `wrapped` into `out`, which has to be exactly `wrapped.len() - 16` bytes long
+///
+/// On failure `out` is zeroed.
+pub fn unwrap_key_into<B>(
+    kek: &Sundae<B, U0>,
+    wrapped: &[u8],
+    header: &[u8],
+    out: &mut [u8],
+) -> Result<(), Error>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+{
+    if wrapped.len() != wrapped_len(out.len()) {
+        return Err(Error);
+    }
+
+    let (tag, ciphertext) = wrapped.split_at(TAG_SIZE);
+    out.copy_from_slice(ciphertext);
+    let result =
+        kek.decrypt_in_place_detached(&Default::default(), header, out, Tag::from_slice(tag));
+    if result.is_err() {
+        out.fill(0);
+    }
+    result
+}
+
+/// Wrap `key` under `kek`, authenticating `header`
+#[cfg(feature = "alloc")]
+pub fn wrap_key<B>(kek: &Sundae<B, U0>, key: &[u8], header: &[u8]) -> Vec<u8>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+{
+    let mut out = alloc::vec![0; wrapped_len(key.len())];
+    // The output length always matches and nonce-less SUNDAE accepts any input
+    wrap_key_into(kek, key, header, &mut out).expect("key wrapping failure");
+    out
+}
+
+/// Unwrap a key wrapped under `kek` with `header`
+#[cfg(feature = "alloc")]
+pub fn unwrap_key<B>(kek: &Sundae<B, U0>, wrapped: &[u8], header: &[u8]) -> Result<Vec<u8>, Error>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+{
+    let len = wrapped.len().checked_sub(TAG_SIZE).ok_or(Error)?;
+    let mut out = alloc::vec![0; len];
+    unwrap_key_into(kek, wrapped, header, &mut out)?;
+    Ok(out)
+}
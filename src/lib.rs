@@ -1,6 +1,10 @@
 //! The [SUNDAE][1] lightweight [Authenticated Encryption and Associated Data (AEAD)][2] cipher.
 //!
 //! SUNDAE made it to round 2 of the [NIST lightweight cryptography competition][3] as part of SUNDAE-GIFT.
+//! The four SUNDAE-GIFT variants are available as `SundaeGift0`, `SundaeGift64`, `SundaeGift96`
+//! and `SundaeGift128` with the `gift` feature.
+//! With AES the same nonce sizes are available as `SundaeAes0`, `SundaeAes` (64-bit),
+//! `SundaeAes96` and `SundaeAes128`, other nonce sizes are rejected at compile time.
 //!
 //! ## Security notes
 //!
@@ -15,8 +19,18 @@
 //!
 //! **USE AT YOUR OWN RISK.**
 //!
+//! ## Compatibility
+//!
+//! Earlier versions put the flags of the initial block in its last byte, multiplied by 2 with the
+//! bytes shifted the other way and multiplied full final blocks by 2 instead of 4. Ciphertexts
+//! and tags they produced do not verify with this version. The current behaviour follows the
+//! SUNDAE-GIFT specification but has not been checked against the official NIST known-answer
+//! tests yet.
+//!
 //! # Usage
 //! ```
+//! # #[cfg(all(feature = "aes", feature = "alloc"))]
+//! # {
 //! use sundae::{SundaeAes, Nonce}; // If you don't know what block cipher to use with SUNDAE choose the pre-defined type with AES, though for smaller devices GIFT would be preferable
 //! use sundae::aead::{Aead, KeyInit};
 //!
@@ -32,13 +46,17 @@
 //!     .expect("decryption failure!"); // NOTE: handle this error to avoic panics!
 //!
 //! assert_eq!(&plaintext, b"plaintext message");
+//! # }
 //! ```
 //!
 //! ## Usage with AAD
 //! SUNDAE can authenticate additional data that is not encrypted alongside with the ciphertext.
 //!
-//! It can also be used as a [MAC][4] algorithm if only additional data is provided without plaintext.
+//! It can also be used as a [MAC][4] algorithm if only additional data is provided without plaintext,
+//! see [`SundaeMac`].
 //! ```
+//! # #[cfg(all(feature = "aes", feature = "alloc"))]
+//! # {
 //! use sundae::{SundaeAes, Nonce}; // If you don't know what block cipher to use with SUNDAE choose the pre-defined type with AES, though a lightweight block cipher like GIFT would be preferable for smaller devices
 //! use sundae::aead::{Aead, KeyInit, Payload};
 //!
@@ -64,6 +82,27 @@
 //!     .expect("decryption failure!"); // NOTE: handle this error to avoid panics!
 //!
 //! assert_eq!(&plaintext, b"this will be encrypted");
+//! # }
+//! ```
+//!
+//! ## Usage as MAC
+//! For plain authenticators [`SundaeMac`] implements the [`digest::Mac`] trait, data can be
+//! supplied incrementally.
+//! ```
+//! # #[cfg(feature = "aes")]
+//! # {
+//! use sundae::{aes::Aes128, SundaeMac};
+//! use sundae::digest::Mac;
+//!
+//! let mut mac: SundaeMac<Aes128> = Mac::new(b"just another key".into());
+//! mac.update(b"authenticated ");
+//! mac.update(b"data");
+//! let tag = mac.finalize().into_bytes();
+//!
+//! let mut mac: SundaeMac<Aes128> = Mac::new(b"just another key".into());
+//! mac.update(b"authenticated data");
+//! mac.verify_slice(&tag).expect("verification failure!");
+//! # }
 //! ```
 //!
 //! ## In-place Usage (eliminates `alloc` requirement)
@@ -82,14 +121,14 @@
 //! and decrypt methods:
 //!
 //! ```
-//! # #[cfg(feature = "heapless")]
+//! # #[cfg(all(feature = "aes", feature = "heapless"))]
 //! # {
 //! use sundae::{SundaeAes, Nonce}; // If you don't know what block cipher to use with SUNDAE choose the pre-defined type with AES, though a lightweight block cipher like GIFT would be preferable for smaller devices
 //! use sundae::aead::{AeadInPlace, KeyInit};
 //! use sundae::aead::heapless::Vec;
 //!
 //! let key = b"just another key";
-//! let cipher = SundaeAes::new(key,into());
+//! let cipher = SundaeAes::new(key.into());
 //!
 //! let nonce = Nonce::from_slice(b"thenonce"); // SUNDAE supports 0-bit, 64-bit, 96-bit and 128-bit NONCEs
 //!
@@ -108,70 +147,179 @@
 //! # }
 //! ```
 //!
+//! ## Online encryption with STREAM
+//!
+//! With the `stream` feature the [`aead::stream`] objects work with SUNDAE using 96-bit or
+//! 128-bit nonces, splitting a long message into segments which are encrypted and decrypted as
+//! they arrive. Reordering, truncating or extending the segments is detected.
+//!
+//! ```
+//! # #[cfg(all(feature = "aes", feature = "alloc", feature = "stream"))]
+//! # {
+//! use sundae::SundaeAes96;
+//! use sundae::aead::{stream::{DecryptorBE32, EncryptorBE32}, KeyInit};
+//!
+//! let cipher = SundaeAes96::new(b"just another key".into());
+//! let nonce = b"7 bytes".into(); // 96-bit nonce minus 5 bytes of counter and last-segment flag
+//!
+//! let mut encryptor = EncryptorBE32::from_aead(cipher.clone(), nonce);
+//! let first = encryptor.encrypt_next(b"first segment".as_ref()).unwrap();
+//! let last = encryptor.encrypt_last(b"last segment".as_ref()).unwrap();
+//!
+//! let mut decryptor = DecryptorBE32::from_aead(cipher, nonce);
+//! assert_eq!(decryptor.decrypt_next(first.as_ref()).unwrap(), b"first segment");
+//! assert_eq!(decryptor.decrypt_last(last.as_ref()).unwrap(), b"last segment");
+//! # }
+//! ```
+//!
 //! [1]: https://csrc.nist.gov/CSRC/media/Projects/lightweight-cryptography/documents/round-2/spec-doc-rnd2/SUNDAE-GIFT-spec-round2.pdf
 //! [2]: https://en.wikipedia.org/wiki/Authenticated_encryption
 //! [3]: https://csrc.nist.gov/projects/lightweight-cryptography
 //! [4]: https://en.wikipedia.org/wiki/Message_authentication_code
 
-#![feature(portable_simd)]
+#![cfg_attr(feature = "simd", feature(portable_simd))]
 #![no_std]
 #![warn(missing_docs, rust_2018_idioms)]
 
-#[cfg(target_arch = "x86")]
-use core::arch::x86 as arch;
-#[cfg(target_arch = "x86_64")]
-use core::arch::x86_64 as arch;
-
-use aead::Payload;
-use arch::{
-    __m128i, _mm_loadu_si128, _mm_set_epi8, _mm_shuffle_epi8, _mm_storeu_si128, _mm_xor_si128,
-};
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "std")]
+extern crate std;
+
+mod backend;
+mod batch;
+mod block_size;
+mod committing;
+mod dynamic;
+mod incremental;
+mod mac;
+mod nonce;
+mod sequence;
+mod truncated;
+mod vector;
+mod vectored;
+
+pub mod envelope;
+#[cfg(feature = "gift")]
+pub mod gift;
+pub mod keywrap;
+
+use backend::Backend;
+use nonce::domain;
 
 pub use aead::{self, AeadCore, AeadInPlace, Error};
+pub use batch::BatchMessage;
+pub use block_size::{SundaeBlockSize, SundaeNonceFor};
 pub use cipher::Key;
+pub use committing::SundaeCommitting;
+pub use digest;
+pub use dynamic::{DynError, SundaeDyn};
+pub use incremental::{SundaeDecryptor, SundaeEncryptor, SundaeKeystream};
+pub use mac::SundaeMac;
+pub use nonce::SundaeNonceSize;
+pub use sequence::{
+    MemoryStore, NonceSequence, NonceStore, SequenceError, SequenceNonceSize, SundaeSealer,
+};
+pub use truncated::{SundaeTagSize, SundaeTrunc};
 
 use cipher::{
-    consts::{U0, U16, U8},
-    generic_array::{ArrayLength, GenericArray},
+    consts::{U0, U16},
+    generic_array::GenericArray,
+    inout::InOutBuf,
+    typenum::Unsigned,
     BlockCipher, BlockEncrypt, BlockSizeUser, KeyInit, KeySizeUser,
 };
 
 use core::marker::PhantomData;
-use core::simd::u8x16;
+use subtle::ConstantTimeEq;
 
 #[cfg(feature = "aes")]
 pub use aes;
 
+#[cfg(feature = "zeroize")]
+pub use zeroize;
+
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
 #[cfg(feature = "aes")]
 use aes::Aes128;
+#[cfg(any(feature = "aes", feature = "gift"))]
+use cipher::consts::U12;
+#[cfg(any(feature = "aes", feature = "gift"))]
+use cipher::consts::U8;
 
 /// SUNDAE nonces
 pub type Nonce<NonceSize> = GenericArray<u8, NonceSize>;
 
-/// SUNDAE tags
-pub type Tag = GenericArray<u8, U16>;
+/// SUNDAE tags, one block of the cipher
+pub type Tag<BlockSize = U16> = GenericArray<u8, BlockSize>;
+
+/// Block of the underlying block cipher
+type Block<BlockSize = U16> = GenericArray<u8, BlockSize>;
 
 /// SUNDAE with AES128 as underlying block cipher
 #[cfg(feature = "aes")]
 pub type SundaeAes = Sundae<Aes128, U8>;
 
+/// SUNDAE with AES128 and no nonce
+#[cfg(feature = "aes")]
+pub type SundaeAes0 = Sundae<Aes128, U0>;
+
+/// SUNDAE with AES128 and a 96-bit nonce
+#[cfg(feature = "aes")]
+pub type SundaeAes96 = Sundae<Aes128, U12>;
+
+/// SUNDAE with AES128 and a 128-bit nonce
+#[cfg(feature = "aes")]
+pub type SundaeAes128 = Sundae<Aes128, U16>;
+
+/// SUNDAE-GIFT-0, SUNDAE with GIFT-128 and no nonce
+#[cfg(feature = "gift")]
+pub type SundaeGift0 = Sundae<gift::Gift128, U0>;
+
+/// SUNDAE-GIFT-64, SUNDAE with GIFT-128 and a 64-bit nonce
+#[cfg(feature = "gift")]
+pub type SundaeGift64 = Sundae<gift::Gift128, U8>;
+
+/// SUNDAE-GIFT-96, SUNDAE with GIFT-128 and a 96-bit nonce
+#[cfg(feature = "gift")]
+pub type SundaeGift96 = Sundae<gift::Gift128, U12>;
+
+/// SUNDAE-GIFT-128, SUNDAE with GIFT-128 and a 128-bit nonce
+#[cfg(feature = "gift")]
+pub type SundaeGift128 = Sundae<gift::Gift128, U16>;
+
 /// Struct representing SUNDAE generic over the underlying block cipher
+///
+/// Block ciphers with 128-bit or 64-bit blocks are supported, see [`SundaeBlockSize`]. The tag
+/// is one block, 8 bytes with a 64-bit cipher. The other constructions of this crate, such as
+/// [`SundaeMac`] or the incremental API, need a 128-bit block cipher.
+///
+/// The multiplication by 2 for 64-bit blocks is this crate's own choice, the CMAC doubling in
+/// GF(2^64), and not checked against the SUNDAE paper. Ciphertexts made with a 64-bit cipher may
+/// not be readable by other SUNDAE implementations.
 #[derive(Clone)]
-pub struct Sundae<B, NonceSize> {
+pub struct Sundae<B, NonceSize: SundaeNonceSize> {
     cipher: B,
+    backend: Backend,
     nonce_size: PhantomData<NonceSize>,
 }
 
 impl<B, NonceSize> KeySizeUser for Sundae<B, NonceSize>
 where
     B: KeyInit,
+    NonceSize: SundaeNonceSize,
 {
     type KeySize = B::KeySize;
 }
 
 impl<B, NonceSize> KeyInit for Sundae<B, NonceSize>
 where
-    B: BlockSizeUser<BlockSize = U16> + BlockEncrypt + KeyInit,
+    B: BlockEncrypt + KeyInit,
+    B::BlockSize: SundaeBlockSize,
+    NonceSize: SundaeNonceFor<B::BlockSize>,
 {
     fn new(key: &Key<Self>) -> Self {
         B::new(key).into()
@@ -180,242 +328,327 @@ where
 
 impl<B, NonceSize> From<B> for Sundae<B, NonceSize>
 where
-    B: BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+    B: BlockEncrypt,
+    B::BlockSize: SundaeBlockSize,
+    NonceSize: SundaeNonceFor<B::BlockSize>,
 {
     fn from(cipher: B) -> Self {
         Self {
             cipher,
+            backend: Backend::detect(),
             nonce_size: PhantomData,
         }
     }
 }
 
+#[cfg(feature = "zeroize")]
+impl<B, NonceSize> Zeroize for Sundae<B, NonceSize>
+where
+    B: Zeroize,
+    NonceSize: SundaeNonceSize,
+{
+    fn zeroize(&mut self) {
+        self.cipher.zeroize();
+    }
+}
+
+// Dropping `Sundae` drops the block cipher, which wipes its key schedule
+#[cfg(feature = "zeroize")]
+impl<B, NonceSize> ZeroizeOnDrop for Sundae<B, NonceSize>
+where
+    B: ZeroizeOnDrop,
+    NonceSize: SundaeNonceSize,
+{
+}
+
 impl<B, NonceSize> AeadCore for Sundae<B, NonceSize>
 where
-    NonceSize: ArrayLength<u8>,
+    B: BlockSizeUser,
+    NonceSize: SundaeNonceSize,
 {
     type NonceSize = NonceSize;
-    type TagSize = U16;
+    type TagSize = B::BlockSize;
     type CiphertextOverhead = U0;
 }
 
 impl<B, NonceSize> AeadInPlace for Sundae<B, NonceSize>
 where
-    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
-    NonceSize: ArrayLength<u8>,
+    B: BlockCipher + BlockEncrypt,
+    B::BlockSize: SundaeBlockSize,
+    NonceSize: SundaeNonceSize,
 {
     fn encrypt_in_place_detached(
         &self,
         nonce: &Nonce<NonceSize>,
         associated_data: &[u8],
         buffer: &mut [u8],
-    ) -> Result<Tag, Error> {
-        unsafe {
-            let ad = [nonce, associated_data].concat();
-            let mut pt_len = buffer.len();
-            let ad_len = ad.len();
-            // Setting the initial value for whether ad is empty or not
-            let b127: i8 = if ad_len > 0 { -128 } else { 0 };
-            // Setting the initial value for whether pt is empty or not
-            let b126: i8 = if pt_len > 0 { 0b01000000 } else { 0 };
-            let b125: i8 = match nonce.len() {
-                0 | 8 => 0,
-                12 | 16 => 0b00100000,
-                _ => return Err(Error),
-            };
-            let b124: i8 = match nonce.len() {
-                0 | 12 => 0,
-                8 | 16 => 0b00010000,
-                _ => return Err(Error),
-            };
-
-            let mut v = self.bc_encrypt(_mm_set_epi8(
-                b127 | b126 | b125 | b124,
-                0,
-                0,
-                0,
-                0,
-                0,
-                0,
-                0,
-                0,
-                0,
-                0,
-                0,
-                0,
-                0,
-                0,
-                0,
-            ));
-            let mut tag = [0u8; 16];
-
-            // Tag computing over associated data
-            if ad_len > 0 {
-                tag = self.mac(&ad, &mut v);
-            }
-            // Tag computing over plaintext
-            if pt_len > 0 {
-                tag = self.mac(buffer, &mut v);
-            }
-
-            if pt_len > 0 {
-                let mut block_start = 0;
-                let mut block_end = 16;
-
-                let mut buf = [0u8; 16];
-                // Encryption procedure for complete blocks
-                while pt_len > 16 {
-                    v = self.bc_encrypt(v);
-
-                    _mm_storeu_si128(
-                        buffer[block_start..block_end].as_ptr() as *mut __m128i,
-                        _mm_xor_si128(
-                            _mm_loadu_si128(
-                                buffer[block_start..block_end].as_ptr() as *const __m128i
-                            ),
-                            v,
-                        ),
-                    );
+    ) -> Result<Tag<B::BlockSize>, Error> {
+        Ok(self.encrypt_detached(domain::<NonceSize>(), nonce, associated_data, buffer))
+    }
 
-                    block_start += 16;
-                    block_end += 16;
-                    pt_len -= 16;
-                }
+    fn decrypt_in_place_detached(
+        &self,
+        nonce: &Nonce<NonceSize>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+        tag: &aead::Tag<Self>,
+    ) -> Result<(), Error> {
+        self.decrypt_detached(domain::<NonceSize>(), nonce, associated_data, buffer, tag)
+    }
+}
 
-                // Encryption for last (maybe partial) block
-                v = self.bc_encrypt(v);
+impl<B, NonceSize> Sundae<B, NonceSize>
+where
+    B: BlockCipher + BlockEncrypt,
+    B::BlockSize: SundaeBlockSize,
+    NonceSize: SundaeNonceSize,
+{
+    /// Encrypt `input` into `output` and return the tag, both buffers must have the same length
+    pub fn encrypt_b2b_detached(
+        &self,
+        nonce: &Nonce<NonceSize>,
+        associated_data: &[u8],
+        input: &[u8],
+        output: &mut [u8],
+    ) -> Result<Tag<B::BlockSize>, Error> {
+        let buffer = InOutBuf::new(input, output).map_err(|_| Error)?;
+        let tag = self.tag(domain::<NonceSize>(), nonce, associated_data, input);
+        self.keystream(&tag, buffer);
+
+        Ok(tag)
+    }
 
-                buf[..pt_len].copy_from_slice(&buffer[block_start..]);
+    /// Decrypt `input` into `output`, both buffers must have the same length
+    ///
+    /// On failure `output` holds a copy of the ciphertext, never unauthenticated plaintext.
+    pub fn decrypt_b2b_detached(
+        &self,
+        nonce: &Nonce<NonceSize>,
+        associated_data: &[u8],
+        input: &[u8],
+        output: &mut [u8],
+        tag: &Tag<B::BlockSize>,
+    ) -> Result<(), Error> {
+        let buffer = InOutBuf::new(input, output).map_err(|_| Error)?;
+        self.open(buffer, tag, |msg| {
+            self.tag(domain::<NonceSize>(), nonce, associated_data, msg)
+        })
+    }
+}
 
-                let tmp = u8x16::from(_mm_xor_si128(
-                    _mm_loadu_si128(buf.as_ptr() as *const __m128i),
-                    v,
-                ));
-                buffer[block_start..].copy_from_slice(&tmp.as_array()[..pt_len]);
-            }
+impl<B, NonceSize> Sundae<B, NonceSize>
+where
+    B: BlockCipher + BlockEncrypt,
+    B::BlockSize: SundaeBlockSize,
+    NonceSize: SundaeNonceSize,
+{
+    // Encryption with a nonce of any supported length, `domain` holds the nonce length bits
+    fn encrypt_detached(
+        &self,
+        domain: u8,
+        nonce: &[u8],
+        associated_data: &[u8],
+        buffer: &mut [u8],
+    ) -> Tag<B::BlockSize> {
+        let tag = self.tag(domain, nonce, associated_data, buffer);
+        self.keystream(&tag, buffer.into());
 
-            Ok(tag.into())
-        }
+        tag
     }
 
-    fn decrypt_in_place_detached(
+    // Decryption with a nonce of any supported length, `domain` holds the nonce length bits
+    fn decrypt_detached(
         &self,
-        nonce: &Nonce<NonceSize>,
+        domain: u8,
+        nonce: &[u8],
         associated_data: &[u8],
         buffer: &mut [u8],
-        tag: &aead::Tag<Self>,
+        tag: &Tag<B::BlockSize>,
     ) -> Result<(), Error> {
-        unsafe {
-            let mut ct_len = buffer.len();
-
-            if ct_len > 0 {
-                let mut block_start = 0;
-                let mut block_end = 16;
-
-                let mut buf = [0u8; 16];
-                let mut block: __m128i;
-                let mut v = _mm_loadu_si128(tag[..].as_ptr() as *const __m128i);
-
-                // Decryption procedure for complete blocks
-                while ct_len > 16 {
-                    v = self.bc_encrypt(v);
-                    block =
-                        _mm_loadu_si128(buffer[block_start..block_end].as_ptr() as *const __m128i);
-                    _mm_storeu_si128(
-                        buffer[block_start..block_end].as_ptr() as *mut __m128i,
-                        _mm_xor_si128(v, block),
-                    );
+        self.open(buffer.into(), tag, |msg| {
+            self.tag(domain, nonce, associated_data, msg)
+        })
+    }
 
-                    block_start += 16;
-                    block_end += 16;
-                    ct_len -= 16;
-                }
+    // Keystream decryption and verification against `tag_of` the recovered plaintext
+    fn open(
+        &self,
+        mut buffer: InOutBuf<'_, '_, u8>,
+        tag: &Tag<B::BlockSize>,
+        tag_of: impl FnOnce(&[u8]) -> Tag<B::BlockSize>,
+    ) -> Result<(), Error> {
+        self.keystream(tag, buffer.reborrow());
+        let buffer = buffer.into_out();
 
-                // Decryption for last (maybe partial) block
-                v = self.bc_encrypt(v);
-                buf[..ct_len].copy_from_slice(&buffer[block_start..]);
-                let tmp = u8x16::from(_mm_xor_si128(
-                    v,
-                    _mm_loadu_si128(buf.as_ptr() as *const __m128i),
-                ));
-                buffer[block_start..].copy_from_slice(&tmp.as_array()[..ct_len]);
-            }
+        // Tag verification over the recovered plaintext
+        let expected = tag_of(buffer);
+        verify(expected, tag, || self.keystream(tag, buffer.into()))
+    }
+
+    // Tag computation over nonce || associated data and the plaintext
+    fn tag(
+        &self,
+        domain: u8,
+        nonce: &[u8],
+        associated_data: &[u8],
+        msg: &[u8],
+    ) -> Tag<B::BlockSize> {
+        let ad_len = nonce.len() + associated_data.len();
+        let mut v = self.initial_block(domain, ad_len > 0, !msg.is_empty());
+
+        // Tag computing over associated data, the nonce is absorbed as its prefix
+        if ad_len > 0 {
+            self.mac(&[nonce, associated_data], &mut v);
+        }
+        // Tag computing over plaintext
+        if !msg.is_empty() {
+            self.mac(&[msg], &mut v);
+        }
+
+        v
+    }
+
+    // Encrypted initial block, `domain` holds the nonce length bits
+    fn initial_block(&self, domain: u8, ad: bool, msg: bool) -> Block<B::BlockSize> {
+        // Setting the initial value for whether ad is empty or not
+        let b127: u8 = if ad { 0b10000000 } else { 0 };
+        // Setting the initial value for whether pt is empty or not
+        let b126: u8 = if msg { 0b01000000 } else { 0 };
+
+        let mut v = Block::<B::BlockSize>::default();
+        v[0] = b127 | b126 | domain;
+        self.bc_encrypt(&mut v);
+        v
+    }
 
-            // Tag verification
-            let payload = Payload {
-                aad: associated_data,
-                msg: buffer,
-            };
-            let test = self::aead::Aead::encrypt(self, nonce, payload).expect("Encryption error");
-            assert!(test[test.len() - 16..] == tag.to_vec());
+    // OFB-style keystream seeded by the tag, used for both encryption and decryption
+    fn keystream(&self, tag: &Tag<B::BlockSize>, buffer: InOutBuf<'_, '_, u8>) {
+        let mut v = tag.clone();
+        let mut buf = Block::<B::BlockSize>::default();
 
-            Ok(())
+        let (blocks, mut rem) = buffer.into_chunks::<B::BlockSize>();
+
+        // Procedure for complete blocks
+        for mut block in blocks {
+            self.bc_encrypt(&mut v);
+            buf = block.clone_in();
+            self.backend.xor(&mut buf, &v);
+            *block.get_out() = buf.clone();
+        }
+
+        // Procedure for last partial block
+        if !rem.is_empty() {
+            self.bc_encrypt(&mut v);
+            rem.xor_in2out(&v[..rem.len()]);
         }
+
+        wipe(&mut buf);
+        wipe(&mut v);
     }
-}
 
-impl<B, NonceSize> Sundae<B, NonceSize>
-where
-    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
-    NonceSize: ArrayLength<u8>,
-{
+    // CBC-MAC style absorption of `parts`, treated as one contiguous string, into `v`
     #[inline]
-    fn mac(&self, buffer: &[u8], v: &mut __m128i) -> [u8; 16] {
-        unsafe {
-            let tag = [0u8; 16];
-            let mut buf = [0u8; 16];
-            let mut block: __m128i;
-            // For Galois field multiplication
-            let mul2 = _mm_set_epi8(14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0, -1);
-            // For Galois field multiplication
-            let xor2 = _mm_set_epi8(
-                -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 15, -1, 15, -1, 15, -1,
-            );
-
-            let mut block_start = 0;
-            let mut block_end = 16;
-            let mut len = buffer.len();
-
-            // Tag computing over complete blocks
-            while len > 16 {
-                block = _mm_loadu_si128(buffer[block_start..block_end].as_ptr() as *const __m128i);
-                *v = self.bc_encrypt(_mm_xor_si128(*v, block));
-
-                len -= 16;
-                block_start += 16;
-                block_end += 16;
-            }
-            // Copy remaining bytes from buffer
-            buf[..len].copy_from_slice(&buffer[block_start..]);
+    fn mac(&self, parts: &[&[u8]], v: &mut Block<B::BlockSize>) {
+        let mut buf = Block::<B::BlockSize>::default();
+        let mut len = 0;
+
+        for part in parts {
+            self.absorb(v, &mut buf, &mut len, part);
+        }
+        self.finish(v, &mut buf, len);
+    }
 
-            // If remaining block is incomplete pad it
-            if len < 16 {
-                buf[len] = 0x80;
+    // Absorb `data` into `v`, the last `len` bytes seen so far are held back in `buf`
+    #[inline]
+    fn absorb(
+        &self,
+        v: &mut Block<B::BlockSize>,
+        buf: &mut Block<B::BlockSize>,
+        len: &mut usize,
+        mut data: &[u8],
+    ) {
+        while !data.is_empty() {
+            // A buffered complete block is only absorbed once more data follows,
+            // the last block needs the finalization in `finish`
+            if *len == B::BlockSize::USIZE {
+                self.backend.xor(v, buf);
+                self.bc_encrypt(v);
+                *len = 0;
             }
 
-            block = _mm_xor_si128(*v, _mm_loadu_si128(buf.as_ptr() as *const __m128i));
-            // If last block smaller than block_size execute galois field multiplication by 2
-            // else execute galois field multiplication by 4
-            if len < 16 {
-                *v = _mm_xor_si128(_mm_shuffle_epi8(block, mul2), _mm_shuffle_epi8(block, xor2));
-                *v = self.bc_encrypt(*v);
-                _mm_storeu_si128(tag.as_ptr() as *mut __m128i, *v);
-            } else {
-                *v = _mm_xor_si128(_mm_shuffle_epi8(block, mul2), _mm_shuffle_epi8(block, xor2));
-                *v = _mm_xor_si128(_mm_shuffle_epi8(block, mul2), _mm_shuffle_epi8(block, xor2));
-                *v = self.bc_encrypt(*v);
-                _mm_storeu_si128(tag.as_ptr() as *mut __m128i, *v);
+            // Tag computing over complete blocks directly from the input
+            if *len == 0 {
+                while data.len() > B::BlockSize::USIZE {
+                    self.backend.xor(
+                        v,
+                        Block::<B::BlockSize>::from_slice(&data[..B::BlockSize::USIZE]),
+                    );
+                    self.bc_encrypt(v);
+                    data = &data[B::BlockSize::USIZE..];
+                }
             }
 
-            tag
+            // Copy remaining bytes into the block buffer
+            let n = (B::BlockSize::USIZE - *len).min(data.len());
+            buf[*len..*len + n].copy_from_slice(&data[..n]);
+            *len += n;
+            data = &data[n..];
+        }
+    }
+
+    // Absorb the held back last block of `len` bytes into `v`
+    #[inline]
+    fn finish(&self, v: &mut Block<B::BlockSize>, buf: &mut Block<B::BlockSize>, len: usize) {
+        // If remaining block is incomplete pad it
+        if len < B::BlockSize::USIZE {
+            buf[len] = 0x80;
+            buf[len + 1..].fill(0);
+        }
+
+        self.backend.xor(buf, v);
+        // If last block smaller than block_size execute galois field multiplication by 2
+        // else execute galois field multiplication by 4
+        if len < B::BlockSize::USIZE {
+            *v = self.backend.double(buf);
+        } else {
+            *v = self.backend.double(&self.backend.double(buf));
         }
+        self.bc_encrypt(v);
+
+        wipe(buf);
     }
 
     // Encryption procedure of the internal block cipher
     #[inline]
-    fn bc_encrypt(&self, _in: __m128i) -> __m128i {
-        let mut tmp = u8x16::from(_in);
-        self.cipher.encrypt_block(tmp.as_mut_array().into());
-        tmp.into()
+    fn bc_encrypt(&self, block: &mut Block<B::BlockSize>) {
+        self.cipher.encrypt_block(block);
+    }
+}
+
+// Compare the `expected` tag with the received one, `restore` puts the ciphertext back on failure
+fn verify<BlockSize: SundaeBlockSize>(
+    mut expected: Tag<BlockSize>,
+    tag: &Tag<BlockSize>,
+    restore: impl FnOnce(),
+) -> Result<(), Error> {
+    // Constant-time comparison so a forgery does not leak how many tag bytes matched
+    let valid: bool = expected.ct_eq(tag).into();
+    wipe(&mut expected);
+
+    if valid {
+        Ok(())
+    } else {
+        // Never release unauthenticated plaintext, restore the ciphertext instead
+        restore();
+        Err(Error)
     }
 }
+
+// Wipe intermediate state before it goes out of scope
+#[inline(always)]
+fn wipe<BlockSize: SundaeBlockSize>(block: &mut Block<BlockSize>) {
+    #[cfg(feature = "zeroize")]
+    block.as_mut_slice().zeroize();
+    #[cfg(not(feature = "zeroize"))]
+    let _ = block;
+}
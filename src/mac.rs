@@ -0,0 +1,130 @@
+//! SUNDAE as a standalone message authentication code.
+
+use crate::{wipe, Block, Sundae};
+
+use cipher::{
+    consts::{U0, U16},
+    BlockCipher, BlockEncrypt, BlockSizeUser, Key, KeyInit, KeySizeUser,
+};
+use digest::{FixedOutput, FixedOutputReset, MacMarker, Output, OutputSizeUser, Reset, Update};
+
+#[cfg(feature = "zeroize")]
+use zeroize::ZeroizeOnDrop;
+
+/// SUNDAE MAC generic over the underlying block cipher
+///
+/// The tag of a message is the tag SUNDAE without a nonce produces for that message as associated
+/// data and an empty plaintext. Data can be fed in any number of [`Update::update`] calls,
+/// [`digest::Mac::verify_slice`] compares tags in constant time.
+#[derive(Clone)]
+pub struct SundaeMac<B> {
+    sundae: Sundae<B, U0>,
+    v: Block,
+    // Last block seen so far, only absorbed once more data follows
+    buf: Block,
+    len: usize,
+    started: bool,
+}
+
+impl<B> KeySizeUser for SundaeMac<B>
+where
+    B: KeyInit,
+{
+    type KeySize = B::KeySize;
+}
+
+impl<B> KeyInit for SundaeMac<B>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt + KeyInit,
+{
+    fn new(key: &Key<Self>) -> Self {
+        B::new(key).into()
+    }
+}
+
+impl<B> From<B> for SundaeMac<B>
+where
+    B: BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+{
+    fn from(cipher: B) -> Self {
+        Self {
+            sundae: cipher.into(),
+            v: Block::default(),
+            buf: Block::default(),
+            len: 0,
+            started: false,
+        }
+    }
+}
+
+impl<B> MacMarker for SundaeMac<B> {}
+
+impl<B> OutputSizeUser for SundaeMac<B> {
+    type OutputSize = U16;
+}
+
+impl<B> Update for SundaeMac<B>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+{
+    fn update(&mut self, data: &[u8]) {
+        if data.is_empty() {
+            return;
+        }
+
+        // Non-empty associated data sets b127 of the initial block
+        if !self.started {
+            self.v = self.sundae.initial_block(0, true, false);
+            self.started = true;
+        }
+
+        self.sundae
+            .absorb(&mut self.v, &mut self.buf, &mut self.len, data);
+    }
+}
+
+impl<B> FixedOutput for SundaeMac<B>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+{
+    fn finalize_into(mut self, out: &mut Output<Self>) {
+        self.finalize_into_reset(out);
+    }
+}
+
+impl<B> Reset for SundaeMac<B> {
+    fn reset(&mut self) {
+        wipe(&mut self.v);
+        wipe(&mut self.buf);
+        self.len = 0;
+        self.started = false;
+    }
+}
+
+impl<B> FixedOutputReset for SundaeMac<B>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+{
+    fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+        if self.started {
+            self.sundae.finish(&mut self.v, &mut self.buf, self.len);
+        } else {
+            // Empty associated data, the tag is the encrypted all-zero initial block
+            self.v = self.sundae.initial_block(0, false, false);
+        }
+
+        *out = self.v;
+        Reset::reset(self);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<B> Drop for SundaeMac<B> {
+    fn drop(&mut self) {
+        Reset::reset(self);
+    }
+}
+
+// Dropping `SundaeMac` wipes the MAC state and drops the block cipher, which wipes its key schedule
+#[cfg(feature = "zeroize")]
+impl<B> ZeroizeOnDrop for SundaeMac<B> where B: ZeroizeOnDrop {}
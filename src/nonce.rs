@@ -0,0 +1,73 @@
+//! Nonce sizes defined by the SUNDAE specification.
+
+use aead::Error;
+use cipher::{
+    consts::{U0, U12, U16, U8},
+    generic_array::ArrayLength,
+};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Nonce sizes SUNDAE supports: 0, 64, 96 and 128 bits
+///
+/// With 64-bit block ciphers only 0-bit and 64-bit nonces are accepted, see
+/// [`SundaeNonceFor`](crate::SundaeNonceFor).
+///
+/// The trait is sealed, other sizes do not compile:
+///
+/// ```compile_fail,E0277
+/// use sundae::{aead::consts::U5, Sundae};
+///
+/// // Whatever the block cipher `B` is
+/// fn broken<B>(cipher: Sundae<B, U5>) {}
+/// ```
+pub trait SundaeNonceSize: ArrayLength<u8> + sealed::Sealed {
+    /// Bit b125 of the initial block, set for 96-bit and 128-bit nonces
+    const B125: u8;
+    /// Bit b124 of the initial block, set for 64-bit and 128-bit nonces
+    const B124: u8;
+}
+
+impl sealed::Sealed for U0 {}
+impl sealed::Sealed for U8 {}
+impl sealed::Sealed for U12 {}
+impl sealed::Sealed for U16 {}
+
+impl SundaeNonceSize for U0 {
+    const B125: u8 = 0;
+    const B124: u8 = 0;
+}
+
+impl SundaeNonceSize for U8 {
+    const B125: u8 = 0;
+    const B124: u8 = 0b00010000;
+}
+
+impl SundaeNonceSize for U12 {
+    const B125: u8 = 0b00100000;
+    const B124: u8 = 0;
+}
+
+impl SundaeNonceSize for U16 {
+    const B125: u8 = 0b00100000;
+    const B124: u8 = 0b00010000;
+}
+
+// Nonce length bits of the initial block
+#[inline]
+pub(crate) fn domain<NonceSize: SundaeNonceSize>() -> u8 {
+    NonceSize::B125 | NonceSize::B124
+}
+
+// Nonce length bits of the initial block for a nonce only known at runtime
+pub(crate) fn nonce_domain(nonce_len: usize) -> Result<u8, Error> {
+    match nonce_len {
+        0 => Ok(domain::<U0>()),
+        8 => Ok(domain::<U8>()),
+        12 => Ok(domain::<U12>()),
+        16 => Ok(domain::<U16>()),
+        _ => Err(Error),
+    }
+}
@@ -0,0 +1,291 @@
+//! Counter nonces for devices without a random number generator.
+
+use crate::{Nonce, Sundae, SundaeNonceSize, Tag};
+
+use aead::{AeadInPlace, Buffer};
+use cipher::{
+    consts::{U12, U16, U8},
+    BlockCipher, BlockEncrypt, BlockSizeUser, Key, KeyInit,
+};
+use core::{fmt, marker::PhantomData};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Nonce sizes [`NonceSequence`] counts in: 64 and 96 bits
+pub trait SequenceNonceSize: SundaeNonceSize + sealed::Sealed {
+    /// Largest counter value a nonce holds
+    const MAX: u128;
+}
+
+impl sealed::Sealed for U8 {}
+impl sealed::Sealed for U12 {}
+
+impl SequenceNonceSize for U8 {
+    const MAX: u128 = u64::MAX as u128;
+}
+
+impl SequenceNonceSize for U12 {
+    const MAX: u128 = (1 << 96) - 1;
+}
+
+/// Persistent storage of the high-water mark of a [`NonceSequence`]
+///
+/// The mark is the counter a sequence resumes from after a restart. A sequence stores a new mark
+/// before it hands out any counter below it, so the store has to be durable once `store` returns,
+/// e.g. written to flash.
+pub trait NonceStore {
+    /// Error of the underlying storage
+    type Error;
+
+    /// Load the stored mark, 0 if none was ever stored
+    fn load(&mut self) -> Result<u128, Self::Error>;
+
+    /// Replace the stored mark
+    fn store(&mut self, mark: u128) -> Result<(), Self::Error>;
+}
+
+/// [`NonceStore`] in memory, for tests and for keys that never outlive the process
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStore {
+    mark: u128,
+}
+
+impl MemoryStore {
+    /// Store holding `mark`, as if a sequence had stored it before
+    pub fn new(mark: u128) -> Self {
+        Self { mark }
+    }
+
+    /// Currently stored mark
+    pub fn mark(&self) -> u128 {
+        self.mark
+    }
+}
+
+impl NonceStore for MemoryStore {
+    type Error = core::convert::Infallible;
+
+    fn load(&mut self) -> Result<u128, Self::Error> {
+        Ok(self.mark)
+    }
+
+    fn store(&mut self, mark: u128) -> Result<(), Self::Error> {
+        self.mark = mark;
+        Ok(())
+    }
+}
+
+/// Errors of [`NonceSequence`] and [`SundaeSealer`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SequenceError<E> {
+    /// Every counter value of the nonce size was used, the key has to be replaced
+    Exhausted,
+    /// The store failed to load or store the high-water mark
+    Store(E),
+    /// The buffer could not hold the tag
+    Aead,
+}
+
+impl<E: fmt::Display> fmt::Display for SequenceError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SequenceError::Exhausted => f.write_str("nonce counter exhausted"),
+            SequenceError::Store(err) => write!(f, "nonce store failure: {}", err),
+            SequenceError::Aead => f.write_str("buffer too small for the tag"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: fmt::Debug + fmt::Display> std::error::Error for SequenceError<E> {}
+
+impl<E> From<aead::Error> for SequenceError<E> {
+    fn from(_: aead::Error) -> Self {
+        SequenceError::Aead
+    }
+}
+
+/// Monotonically increasing big-endian counter nonces
+///
+/// Counters are reserved in windows: when the current window is used up the sequence stores the
+/// end of the next one as the high-water mark, before handing out its first counter. A restart
+/// resumes from the stored mark, skipping whatever was left of the window, so a nonce is never
+/// repeated. Larger windows mean fewer writes to the store and more counters lost per restart.
+///
+/// ```
+/// use sundae::{MemoryStore, NonceSequence};
+/// use sundae::aead::consts::U8;
+///
+/// let mut sequence = NonceSequence::<U8, _>::with_window(MemoryStore::default(), 100).unwrap();
+/// assert_eq!(sequence.next_nonce().unwrap().as_slice(), &[0, 0, 0, 0, 0, 0, 0, 0]);
+/// assert_eq!(sequence.next_nonce().unwrap().as_slice(), &[0, 0, 0, 0, 0, 0, 0, 1]);
+/// assert_eq!(sequence.store().mark(), 100);
+/// ```
+#[derive(Clone, Debug)]
+pub struct NonceSequence<NonceSize: SequenceNonceSize, S> {
+    store: S,
+    // Next counter to hand out, MAX + 1 once exhausted
+    next: u128,
+    // Stored high-water mark, end of the current window
+    mark: u128,
+    window: u128,
+    nonce_size: PhantomData<NonceSize>,
+}
+
+impl<NonceSize, S> NonceSequence<NonceSize, S>
+where
+    NonceSize: SequenceNonceSize,
+    S: NonceStore,
+{
+    /// Sequence resuming from the mark in `store`, storing a new mark for every nonce
+    pub fn new(store: S) -> Result<Self, SequenceError<S::Error>> {
+        Self::with_window(store, 1)
+    }
+
+    /// Sequence resuming from the mark in `store`, reserving `window` counters per store
+    ///
+    /// A `window` of 0 is treated as 1.
+    pub fn with_window(mut store: S, window: u64) -> Result<Self, SequenceError<S::Error>> {
+        let mark = store.load().map_err(SequenceError::Store)?;
+        Ok(Self {
+            store,
+            next: mark,
+            mark,
+            window: window.max(1).into(),
+            nonce_size: PhantomData,
+        })
+    }
+
+    /// Next nonce, failing once all counters are used
+    pub fn next_nonce(&mut self) -> Result<Nonce<NonceSize>, SequenceError<S::Error>> {
+        if self.next > NonceSize::MAX {
+            return Err(SequenceError::Exhausted);
+        }
+        if self.next >= self.mark {
+            let mark = (self.next + self.window).min(NonceSize::MAX + 1);
+            self.store.store(mark).map_err(SequenceError::Store)?;
+            self.mark = mark;
+        }
+
+        let bytes = self.next.to_be_bytes();
+        let nonce = Nonce::<NonceSize>::clone_from_slice(&bytes[16 - NonceSize::USIZE..]);
+        self.next += 1;
+        Ok(nonce)
+    }
+
+    /// Nonces left before the sequence is exhausted
+    pub fn remaining(&self) -> u128 {
+        (NonceSize::MAX + 1).saturating_sub(self.next)
+    }
+
+    /// Underlying store
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Give up the sequence and return its store
+    pub fn into_store(self) -> S {
+        self.store
+    }
+}
+
+/// SUNDAE paired with a [`NonceSequence`], so nonces are never passed by hand
+///
+/// Sealing returns the nonce next to the tag, the receiver needs both to decrypt with
+/// [`Sundae`] and the same key.
+///
+/// ```
+/// # #[cfg(feature = "aes")]
+/// # {
+/// use sundae::{aes::Aes128, MemoryStore, SundaeAes, SundaeSealer};
+/// use sundae::aead::{consts::U8, AeadInPlace, KeyInit};
+///
+/// let mut sealer =
+///     SundaeSealer::<Aes128, U8, _>::new(b"just another key".into(), MemoryStore::default())
+///         .unwrap();
+///
+/// let mut buffer = *b"plaintext message";
+/// let (nonce, tag) = sealer.seal_in_place_detached(b"", &mut buffer).unwrap();
+///
+/// let receiver = SundaeAes::new(b"just another key".into());
+/// receiver
+///     .decrypt_in_place_detached(&nonce, b"", &mut buffer, &tag)
+///     .expect("decryption failure!");
+/// assert_eq!(&buffer, b"plaintext message");
+/// # }
+/// ```
+pub struct SundaeSealer<B, NonceSize: SequenceNonceSize, S> {
+    sundae: Sundae<B, NonceSize>,
+    sequence: NonceSequence<NonceSize, S>,
+}
+
+impl<B, NonceSize, S> SundaeSealer<B, NonceSize, S>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+    NonceSize: SequenceNonceSize,
+    S: NonceStore,
+{
+    /// Sealer for `key`, resuming the nonce sequence from `store`
+    pub fn new(key: &Key<B>, store: S) -> Result<Self, SequenceError<S::Error>>
+    where
+        B: KeyInit,
+    {
+        Ok(Self::from_parts(
+            Sundae::new(key),
+            NonceSequence::new(store)?,
+        ))
+    }
+
+    /// Sealer from a cipher and a sequence, the sequence must only ever be used with this key
+    pub fn from_parts(sundae: Sundae<B, NonceSize>, sequence: NonceSequence<NonceSize, S>) -> Self {
+        Self { sundae, sequence }
+    }
+
+    /// Encrypt `buffer` in place under the next nonce, returning nonce and tag
+    pub fn seal_in_place_detached(
+        &mut self,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(Nonce<NonceSize>, Tag), SequenceError<S::Error>> {
+        let nonce = self.sequence.next_nonce()?;
+        let tag = self
+            .sundae
+            .encrypt_in_place_detached(&nonce, associated_data, buffer)?;
+        Ok((nonce, tag))
+    }
+
+    /// Encrypt `buffer` in place under the next nonce and append the tag, returning the nonce
+    pub fn seal_in_place(
+        &mut self,
+        associated_data: &[u8],
+        buffer: &mut dyn Buffer,
+    ) -> Result<Nonce<NonceSize>, SequenceError<S::Error>> {
+        // Make room for the tag first, a buffer without room for it must not use up a nonce
+        let len = buffer.len();
+        buffer.extend_from_slice(&<Tag>::default())?;
+
+        let result = self.seal_in_place_detached(associated_data, &mut buffer.as_mut()[..len]);
+        match result {
+            Ok((nonce, tag)) => {
+                buffer.as_mut()[len..].copy_from_slice(&tag);
+                Ok(nonce)
+            }
+            Err(e) => {
+                buffer.truncate(len);
+                Err(e)
+            }
+        }
+    }
+
+    /// Cipher for decryption, sealing directly with it bypasses the sequence
+    pub fn cipher(&self) -> &Sundae<B, NonceSize> {
+        &self.sundae
+    }
+
+    /// Underlying nonce sequence
+    pub fn sequence(&self) -> &NonceSequence<NonceSize, S> {
+        &self.sequence
+    }
+}
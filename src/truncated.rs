@@ -0,0 +1,244 @@
+//! SUNDAE with tags shorter than a block.
+
+use crate::{nonce::domain, verify, wipe, Nonce, Sundae, SundaeNonceSize, Tag};
+
+use aead::{AeadCore, AeadInPlace, Error};
+use cipher::{
+    consts::{U0, U10, U11, U12, U13, U14, U15, U16, U4, U5, U6, U7, U8, U9},
+    generic_array::ArrayLength,
+    BlockCipher, BlockEncrypt, BlockSizeUser, Key, KeyInit, KeySizeUser,
+};
+use core::marker::PhantomData;
+
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+// Domain of the nonce and associated data block mixed into the keystream seed
+const SEED: u8 = 0b00000110;
+
+mod sealed {
+    pub trait Sealed<NonceSize> {}
+}
+
+/// Tag sizes [`SundaeTrunc`] supports with `NonceSize`: 4 to 16 bytes with a nonce, only 16
+/// bytes without
+///
+/// The trait is sealed, other sizes do not compile:
+///
+/// ```compile_fail,E0277
+/// use sundae::{aead::consts::{U2, U8}, SundaeTrunc};
+///
+/// // Whatever the block cipher `B` is
+/// fn broken<B>(cipher: SundaeTrunc<B, U8, U2>) {}
+/// ```
+///
+/// Neither do tags shorter than a block without a nonce, as every message with the same
+/// associated data would get the same keystream seed for the same tag:
+///
+/// ```compile_fail,E0277
+/// use sundae::{aead::consts::{U0, U4}, SundaeTrunc};
+///
+/// fn broken<B>(cipher: SundaeTrunc<B, U0, U4>) {}
+/// ```
+pub trait SundaeTagSize<NonceSize: SundaeNonceSize>:
+    ArrayLength<u8> + sealed::Sealed<NonceSize>
+{
+}
+
+// Full-length tags are plain SUNDAE, with or without a nonce
+impl<NonceSize: SundaeNonceSize> sealed::Sealed<NonceSize> for U16 {}
+impl<NonceSize: SundaeNonceSize> SundaeTagSize<NonceSize> for U16 {}
+
+macro_rules! tag_sizes {
+    ($($size:ty),*) => {
+        $(
+            impl sealed::Sealed<U8> for $size {}
+            impl sealed::Sealed<U12> for $size {}
+            impl sealed::Sealed<U16> for $size {}
+            impl SundaeTagSize<U8> for $size {}
+            impl SundaeTagSize<U12> for $size {}
+            impl SundaeTagSize<U16> for $size {}
+        )*
+    };
+}
+
+tag_sizes!(U4, U5, U6, U7, U8, U9, U10, U11, U12, U13, U14, U15);
+
+/// SUNDAE with the tag truncated to `TagSize` bytes
+///
+/// The tag is the prefix of the SUNDAE tag. The receiver only gets to see that prefix, so the
+/// keystream can not be seeded with the full tag as in SUNDAE. Seeding it with the truncated tag
+/// alone would repeat the keystream after about 2^4t messages with t-byte tags. Instead the seed
+/// is the truncated tag padded with zeros, XORed with a SUNDAE tag over the nonce and associated
+/// data in a domain of its own, which the receiver computes before decrypting. This is not part
+/// of the SUNDAE specification. With 16-byte tags the full tag is the seed and messages are
+/// identical to [`Sundae`].
+///
+/// A forgery succeeds with probability 2^-8t per attempt for t-byte tags. Keystreams only repeat
+/// for two messages with the same nonce, associated data and tag, so nonces must not repeat
+/// either: after about 2^4t messages under one nonce two of them share a keystream. Without a
+/// nonce that bound would apply to all messages with the same associated data, so tags shorter
+/// than 16 bytes need a nonce, see [`SundaeTagSize`].
+///
+/// ```
+/// # #[cfg(feature = "aes")]
+/// # {
+/// use sundae::{aes::Aes128, SundaeTrunc};
+/// use sundae::aead::{consts::{U4, U8}, AeadInPlace, KeyInit};
+///
+/// let cipher = SundaeTrunc::<Aes128, U8, U4>::new(b"just another key".into());
+/// let nonce = b"thenonce".into();
+///
+/// let mut buffer = *b"plaintext message";
+/// let tag = cipher.encrypt_in_place_detached(nonce, b"", &mut buffer).unwrap();
+/// assert_eq!(tag.len(), 4);
+///
+/// cipher
+///     .decrypt_in_place_detached(nonce, b"", &mut buffer, &tag)
+///     .expect("decryption failure!");
+/// assert_eq!(&buffer, b"plaintext message");
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct SundaeTrunc<B, NonceSize: SundaeNonceSize, TagSize: SundaeTagSize<NonceSize>> {
+    sundae: Sundae<B, NonceSize>,
+    tag_size: PhantomData<TagSize>,
+}
+
+impl<B, NonceSize, TagSize> KeySizeUser for SundaeTrunc<B, NonceSize, TagSize>
+where
+    B: KeyInit,
+    NonceSize: SundaeNonceSize,
+    TagSize: SundaeTagSize<NonceSize>,
+{
+    type KeySize = B::KeySize;
+}
+
+impl<B, NonceSize, TagSize> KeyInit for SundaeTrunc<B, NonceSize, TagSize>
+where
+    B: BlockSizeUser<BlockSize = U16> + BlockEncrypt + KeyInit,
+    NonceSize: SundaeNonceSize,
+    TagSize: SundaeTagSize<NonceSize>,
+{
+    fn new(key: &Key<Self>) -> Self {
+        B::new(key).into()
+    }
+}
+
+impl<B, NonceSize, TagSize> From<B> for SundaeTrunc<B, NonceSize, TagSize>
+where
+    B: BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+    NonceSize: SundaeNonceSize,
+    TagSize: SundaeTagSize<NonceSize>,
+{
+    fn from(cipher: B) -> Self {
+        Self {
+            sundae: cipher.into(),
+            tag_size: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<B, NonceSize, TagSize> Zeroize for SundaeTrunc<B, NonceSize, TagSize>
+where
+    B: Zeroize,
+    NonceSize: SundaeNonceSize,
+    TagSize: SundaeTagSize<NonceSize>,
+{
+    fn zeroize(&mut self) {
+        self.sundae.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<B, NonceSize, TagSize> ZeroizeOnDrop for SundaeTrunc<B, NonceSize, TagSize>
+where
+    B: ZeroizeOnDrop,
+    NonceSize: SundaeNonceSize,
+    TagSize: SundaeTagSize<NonceSize>,
+{
+}
+
+impl<B, NonceSize, TagSize> AeadCore for SundaeTrunc<B, NonceSize, TagSize>
+where
+    NonceSize: SundaeNonceSize,
+    TagSize: SundaeTagSize<NonceSize>,
+{
+    type NonceSize = NonceSize;
+    type TagSize = TagSize;
+    type CiphertextOverhead = U0;
+}
+
+impl<B, NonceSize, TagSize> AeadInPlace for SundaeTrunc<B, NonceSize, TagSize>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+    NonceSize: SundaeNonceSize,
+    TagSize: SundaeTagSize<NonceSize>,
+{
+    fn encrypt_in_place_detached(
+        &self,
+        nonce: &Nonce<NonceSize>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<aead::Tag<Self>, Error> {
+        let full = self
+            .sundae
+            .tag(domain::<NonceSize>(), nonce, associated_data, buffer);
+        let tag = aead::Tag::<Self>::clone_from_slice(&full[..TagSize::USIZE]);
+
+        let mut seed = self.seed(nonce, associated_data, &tag);
+        self.sundae.keystream(&seed, buffer.into());
+        wipe(&mut seed);
+
+        Ok(tag)
+    }
+
+    fn decrypt_in_place_detached(
+        &self,
+        nonce: &Nonce<NonceSize>,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+        tag: &aead::Tag<Self>,
+    ) -> Result<(), Error> {
+        let mut padded = Tag::default();
+        padded[..TagSize::USIZE].copy_from_slice(tag);
+        let mut seed = self.seed(nonce, associated_data, tag);
+        self.sundae.keystream(&seed, buffer.into());
+
+        // Comparing the padded blocks compares the prefix, the padding always matches
+        let mut expected = self
+            .sundae
+            .tag(domain::<NonceSize>(), nonce, associated_data, buffer);
+        expected[TagSize::USIZE..].fill(0);
+        let result = verify(expected, &padded, || {
+            self.sundae.keystream(&seed, buffer.into())
+        });
+        wipe(&mut seed);
+
+        result
+    }
+}
+
+impl<B, NonceSize, TagSize> SundaeTrunc<B, NonceSize, TagSize>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+    NonceSize: SundaeNonceSize,
+    TagSize: SundaeTagSize<NonceSize>,
+{
+    // Keystream seed, the padded truncated tag XOR a tag over the nonce and associated data
+    fn seed(&self, nonce: &[u8], associated_data: &[u8], tag: &aead::Tag<Self>) -> Tag {
+        let mut seed = Tag::default();
+        seed[..TagSize::USIZE].copy_from_slice(tag);
+
+        if TagSize::USIZE < 16 {
+            let mut header =
+                self.sundae
+                    .tag(domain::<NonceSize>() | SEED, nonce, associated_data, &[]);
+            self.sundae.backend.xor(&mut seed, &header);
+            wipe(&mut header);
+        }
+
+        seed
+    }
+}
@@ -0,0 +1,110 @@
+//! Encryption of messages split over several buffers.
+
+use crate::{
+    incremental::SundaeKeystream, nonce::domain, verify, Block, Nonce, Sundae, SundaeNonceSize, Tag,
+};
+
+use aead::Error;
+use cipher::{consts::U16, BlockCipher, BlockEncrypt, BlockSizeUser};
+use core::ops::DerefMut;
+
+impl<B, NonceSize> Sundae<B, NonceSize>
+where
+    B: BlockCipher + BlockSizeUser<BlockSize = U16> + BlockEncrypt,
+    NonceSize: SundaeNonceSize,
+{
+    /// Encrypt the message made of the concatenated `segments` in place and return the tag
+    ///
+    /// Segments can have any length, blocks straddling segment boundaries are handled, and the
+    /// output is the same as encrypting the concatenation with
+    /// [`aead::AeadInPlace::encrypt_in_place_detached`]. Anything dereferencing to `[u8]` works
+    /// as a segment, e.g. `&mut [u8]` or `std::io::IoSliceMut`.
+    ///
+    /// ```
+    /// # #[cfg(feature = "aes")]
+    /// # {
+    /// use sundae::SundaeAes;
+    /// use sundae::aead::KeyInit;
+    ///
+    /// let cipher = SundaeAes::new(b"just another key".into());
+    /// let nonce = b"thenonce".into();
+    ///
+    /// let (mut header, mut payload) = (*b"plaintext", *b" message");
+    /// let tag = cipher.encrypt_vectored(nonce, b"", &mut [&mut header[..], &mut payload[..]]);
+    ///
+    /// cipher
+    ///     .decrypt_vectored(nonce, b"", &mut [&mut header[..], &mut payload[..]], &tag)
+    ///     .expect("decryption failure!");
+    /// assert_eq!((&header, &payload), (b"plaintext", b" message"));
+    /// # }
+    /// ```
+    pub fn encrypt_vectored<S>(
+        &self,
+        nonce: &Nonce<NonceSize>,
+        associated_data: &[u8],
+        segments: &mut [S],
+    ) -> Tag
+    where
+        S: DerefMut<Target = [u8]>,
+    {
+        let tag = self.vectored_tag(nonce, associated_data, segments);
+        self.vectored_keystream(&tag, segments);
+
+        tag
+    }
+
+    /// Decrypt the message made of the concatenated `segments` in place
+    ///
+    /// On failure the segments keep the ciphertext.
+    pub fn decrypt_vectored<S>(
+        &self,
+        nonce: &Nonce<NonceSize>,
+        associated_data: &[u8],
+        segments: &mut [S],
+        tag: &Tag,
+    ) -> Result<(), Error>
+    where
+        S: DerefMut<Target = [u8]>,
+    {
+        self.vectored_keystream(tag, segments);
+
+        // Tag verification over the recovered plaintext
+        let expected = self.vectored_tag(nonce, associated_data, segments);
+        verify(expected, tag, || self.vectored_keystream(tag, segments))
+    }
+
+    // Tag computation with the message absorbed segment by segment
+    fn vectored_tag<S>(&self, nonce: &[u8], associated_data: &[u8], segments: &[S]) -> Tag
+    where
+        S: DerefMut<Target = [u8]>,
+    {
+        let ad_len = nonce.len() + associated_data.len();
+        let msg = segments.iter().any(|segment| !segment.is_empty());
+        let mut v = self.initial_block(domain::<NonceSize>(), ad_len > 0, msg);
+
+        if ad_len > 0 {
+            self.mac(&[nonce, associated_data], &mut v);
+        }
+        if msg {
+            let mut buf = Block::default();
+            let mut len = 0;
+            for segment in segments {
+                self.absorb(&mut v, &mut buf, &mut len, segment);
+            }
+            self.finish(&mut v, &mut buf, len);
+        }
+
+        v
+    }
+
+    // Keystream continued across segment boundaries
+    fn vectored_keystream<S>(&self, tag: &Tag, segments: &mut [S])
+    where
+        S: DerefMut<Target = [u8]>,
+    {
+        let mut keystream = SundaeKeystream::new(self, tag);
+        for segment in segments {
+            keystream.apply_keystream(segment);
+        }
+    }
+}
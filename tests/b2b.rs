@@ -0,0 +1,57 @@
+#![cfg(feature = "aes")]
+
+use sundae::{
+    aead::{AeadInPlace, KeyInit},
+    SundaeAes,
+};
+
+#[test]
+fn b2b_test() {
+    let cipher = SundaeAes::new(b"just another key".into());
+    let nonce = b"thenonce".into();
+    let plaintext: Vec<u8> = (0..100).collect();
+
+    for len in [0, 1, 15, 16, 17, 32, 100] {
+        let input = &plaintext[..len];
+
+        let mut expected = input.to_vec();
+        let tag = cipher
+            .encrypt_in_place_detached(nonce, b"ad", &mut expected)
+            .unwrap();
+
+        let mut ciphertext = vec![0; len];
+        let t = cipher
+            .encrypt_b2b_detached(nonce, b"ad", input, &mut ciphertext)
+            .unwrap();
+        assert_eq!(t, tag);
+        assert_eq!(ciphertext, expected);
+
+        let mut output = vec![0; len];
+        cipher
+            .decrypt_b2b_detached(nonce, b"ad", &ciphertext, &mut output, &tag)
+            .unwrap();
+        assert_eq!(output, input);
+
+        // Failed decryption does not leave plaintext behind
+        let mut output = vec![0; len];
+        assert!(cipher
+            .decrypt_b2b_detached(nonce, b"da", &ciphertext, &mut output, &tag)
+            .is_err());
+        assert_eq!(output, ciphertext);
+    }
+}
+
+#[test]
+fn length_mismatch_test() {
+    let cipher = SundaeAes::new(b"just another key".into());
+    let nonce = b"thenonce".into();
+
+    let mut output = [0u8; 16];
+    assert!(cipher
+        .encrypt_b2b_detached(nonce, b"", &[0; 17], &mut output)
+        .is_err());
+    assert!(cipher
+        .decrypt_b2b_detached(nonce, b"", &[0; 15], &mut output, &Default::default())
+        .is_err());
+    assert_eq!(output, [0; 16]);
+}
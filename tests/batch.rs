@@ -0,0 +1,111 @@
+#![cfg(feature = "aes")]
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use sundae::{
+    aead::{AeadInPlace, KeyInit},
+    Nonce, SundaeAes, Tag,
+};
+
+const KEY: &[u8; 16] = b"just another key";
+
+// Batches of any size with messages of mixed lengths match encrypting one by one
+#[test]
+fn sequential_test() {
+    let cipher = SundaeAes::new(KEY.into());
+    let mut rng = StdRng::seed_from_u64(42);
+
+    for count in [0, 1, 3, 8, 9, 20] {
+        let nonces: Vec<[u8; 8]> = (0..count).map(|_| rng.gen()).collect();
+        let ads: Vec<Vec<u8>> = (0..count)
+            .map(|_| (0..rng.gen_range(0..40)).map(|_| rng.gen()).collect())
+            .collect();
+        let plaintexts: Vec<Vec<u8>> = (0..count)
+            .map(|_| (0..rng.gen_range(0..70)).map(|_| rng.gen()).collect())
+            .collect();
+
+        let mut expected = plaintexts.clone();
+        let expected_tags: Vec<Tag> = (0..count)
+            .map(|i| {
+                cipher
+                    .encrypt_in_place_detached(&nonces[i].into(), &ads[i], &mut expected[i])
+                    .unwrap()
+            })
+            .collect();
+
+        let mut buffers = plaintexts.clone();
+        let mut messages: Vec<(&Nonce<_>, &[u8], &mut [u8])> = nonces
+            .iter()
+            .zip(&ads)
+            .zip(&mut buffers)
+            .map(|((nonce, ad), buffer)| (nonce.into(), &ad[..], &mut buffer[..]))
+            .collect();
+        let mut tags = vec![Tag::default(); count];
+        cipher.encrypt_batch_into(&mut messages, &mut tags).unwrap();
+
+        assert_eq!(tags, expected_tags);
+        assert_eq!(buffers, expected);
+    }
+}
+
+// Exact block multiples of nonce, associated data and message
+#[test]
+fn block_boundary_test() {
+    let cipher = SundaeAes::new(KEY.into());
+    let nonce = b"thenonce".into();
+
+    let ads = [&b""[..], b"12345678", b"associated data!associated data!"];
+    let plaintexts: Vec<Vec<u8>> = [0, 1, 15, 16, 17, 32, 33].map(|len| vec![7; len]).into();
+
+    for ad in ads {
+        let mut expected = plaintexts.clone();
+        let expected_tags: Vec<Tag> = expected
+            .iter_mut()
+            .map(|buffer| cipher.encrypt_in_place_detached(nonce, ad, buffer).unwrap())
+            .collect();
+
+        let mut buffers = plaintexts.clone();
+        let mut messages: Vec<(&Nonce<_>, &[u8], &mut [u8])> = buffers
+            .iter_mut()
+            .map(|buffer| (nonce, ad, &mut buffer[..]))
+            .collect();
+        let mut tags = vec![Tag::default(); messages.len()];
+        cipher.encrypt_batch_into(&mut messages, &mut tags).unwrap();
+
+        assert_eq!(tags, expected_tags);
+        assert_eq!(buffers, expected);
+    }
+}
+
+#[test]
+fn tag_count_test() {
+    let cipher = SundaeAes::new(KEY.into());
+    let mut buffer = *b"plaintext";
+
+    let mut tags = [Tag::default(); 2];
+    assert!(cipher
+        .encrypt_batch_into(&mut [(b"thenonce".into(), b"", &mut buffer[..])], &mut tags)
+        .is_err());
+    assert_eq!(&buffer, b"plaintext");
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn batch_test() {
+    let cipher = SundaeAes::new(KEY.into());
+    let nonce = b"thenonce".into();
+    let (mut first, mut second) = (*b"first", *b"second");
+
+    let tags = cipher.encrypt_batch(&mut [
+        (nonce, b"ad", &mut first[..]),
+        (nonce, b"", &mut second[..]),
+    ]);
+    assert_eq!(tags.len(), 2);
+
+    cipher
+        .decrypt_in_place_detached(nonce, b"ad", &mut first, &tags[0])
+        .unwrap();
+    cipher
+        .decrypt_in_place_detached(nonce, b"", &mut second, &tags[1])
+        .unwrap();
+    assert_eq!((&first, &second), (b"first", b"second"));
+}
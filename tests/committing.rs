@@ -0,0 +1,283 @@
+use cipher::{
+    consts::{U1, U16},
+    inout::InOut,
+    Block, BlockBackend, BlockCipher, BlockClosure, BlockEncrypt, BlockSizeUser, Key, KeyInit,
+    KeySizeUser, ParBlocksSizeUser,
+};
+use sundae::{aead::AeadInPlace, Sundae, SundaeCommitting, Tag};
+
+/// Toy cipher E(x) = a * x + b over GF(2^128), making SUNDAE affine in the ciphertext
+#[derive(Clone)]
+struct Affine {
+    a: u128,
+    b: u128,
+}
+
+impl KeySizeUser for Affine {
+    type KeySize = U16;
+}
+
+impl KeyInit for Affine {
+    fn new(key: &Key<Self>) -> Self {
+        let k = u128::from_be_bytes((*key).into());
+        Self {
+            a: k | 1,
+            b: k.rotate_left(64) ^ 0x5555,
+        }
+    }
+}
+
+impl BlockSizeUser for Affine {
+    type BlockSize = U16;
+}
+
+impl BlockCipher for Affine {}
+
+impl BlockEncrypt for Affine {
+    fn encrypt_with_backend(&self, f: impl BlockClosure<BlockSize = U16>) {
+        f.call(&mut AffineBackend(self))
+    }
+}
+
+struct AffineBackend<'a>(&'a Affine);
+
+impl<'a> BlockSizeUser for AffineBackend<'a> {
+    type BlockSize = U16;
+}
+
+impl<'a> ParBlocksSizeUser for AffineBackend<'a> {
+    type ParBlocksSize = U1;
+}
+
+impl<'a> BlockBackend for AffineBackend<'a> {
+    fn proc_block(&mut self, mut block: InOut<'_, '_, Block<Self>>) {
+        let x = u128::from_be_bytes((*block.get_in()).into());
+        *block.get_out() = (gf_mul(self.0.a, x) ^ self.0.b).to_be_bytes().into();
+    }
+}
+
+// Multiplication modulo x^128 + x^7 + x^2 + x + 1
+fn gf_mul(mut a: u128, b: u128) -> u128 {
+    let mut r = 0;
+    for i in 0..128 {
+        if (b >> i) & 1 == 1 {
+            r ^= a;
+        }
+        a = (a << 1) ^ if a >> 127 == 1 { 0x87 } else { 0 };
+    }
+    r
+}
+
+const BLOCKS: usize = 3;
+const BITS: usize = BLOCKS * 128;
+
+type Plain = Sundae<Affine, U16>;
+type Committing = SundaeCommitting<Affine, U16>;
+
+// SUNDAE tag under `key` of the plaintext `ciphertext` decrypts to with keystream seeded by `tag`
+fn tag_of(key: &[u8; 16], nonce: &[u8; 16], tag: &Tag, ciphertext: &[u8]) -> u128 {
+    let cipher = Affine::new(key.into());
+    let mut plaintext = ciphertext.to_vec();
+    let mut v = *tag;
+    for block in plaintext.chunks_mut(16) {
+        cipher.encrypt_block(&mut v);
+        block.iter_mut().zip(v.iter()).for_each(|(p, k)| *p ^= k);
+    }
+
+    let t = Plain::new(key.into())
+        .encrypt_in_place_detached(nonce.into(), b"", &mut plaintext)
+        .unwrap();
+    u128::from_be_bytes(t.into())
+}
+
+// Commitment block under `key`
+fn commitment_of(key: &[u8; 16], nonce: &[u8; 16]) -> u128 {
+    let t = Committing::new(key.into())
+        .encrypt_in_place_detached(nonce.into(), b"", &mut [])
+        .unwrap();
+    u128::from_be_bytes(t[..16].try_into().unwrap())
+}
+
+// Input of `bits` bits with `eval(input) == target`, for `eval` affine over GF(2), found by
+// Gaussian elimination
+fn solve(bits: usize, eval: impl Fn(&[u8]) -> Vec<u128>, target: &[u128]) -> Vec<u8> {
+    let zero = vec![0u8; bits / 8];
+    let base = eval(&zero);
+
+    // Row r holds the coefficients of output bit r % 128 of word r / 128 and the right-hand side
+    let mut rows = vec![(vec![0u128; bits.div_ceil(128)], false); 128 * target.len()];
+    for j in 0..bits {
+        let mut unit = zero.clone();
+        unit[j / 8] = 0x80 >> (j % 8);
+        let column = eval(&unit);
+        for (r, row) in rows.iter_mut().enumerate() {
+            if ((column[r / 128] ^ base[r / 128]) >> (r % 128)) & 1 == 1 {
+                row.0[j / 128] |= 1 << (j % 128);
+            }
+        }
+    }
+    for (r, row) in rows.iter_mut().enumerate() {
+        row.1 = ((target[r / 128] ^ base[r / 128]) >> (r % 128)) & 1 == 1;
+    }
+
+    let mut pivots = Vec::new();
+    for j in 0..bits {
+        let bit = |row: &(Vec<u128>, bool)| (row.0[j / 128] >> (j % 128)) & 1 == 1;
+        let r = pivots.len();
+        let Some(p) = (r..rows.len()).find(|&p| bit(&rows[p])) else {
+            continue;
+        };
+        rows.swap(r, p);
+        let pivot = rows[r].clone();
+        for (q, row) in rows.iter_mut().enumerate() {
+            if q != r && bit(row) {
+                row.0.iter_mut().zip(&pivot.0).for_each(|(w, p)| *w ^= p);
+                row.1 ^= pivot.1;
+            }
+        }
+        pivots.push(j);
+    }
+    assert!(rows[pivots.len()..].iter().all(|row| !row.1), "no solution");
+
+    let mut input = zero;
+    for (row, &j) in rows.iter().zip(&pivots) {
+        if row.1 {
+            input[j / 8] |= 0x80 >> (j % 8);
+        }
+    }
+    input
+}
+
+// Ciphertext valid under both keys with the given nonce and tag
+fn multi_key_ciphertext(k1: &[u8; 16], k2: &[u8; 16], nonce: &[u8; 16], tag: &Tag) -> Vec<u8> {
+    let target = u128::from_be_bytes((*tag).into());
+    solve(
+        BITS,
+        |c| vec![tag_of(k1, nonce, tag, c), tag_of(k2, nonce, tag, c)],
+        &[target, target],
+    )
+}
+
+// Nonce under which both keys give the same commitment
+fn colliding_nonce(k1: &[u8; 16], k2: &[u8; 16]) -> [u8; 16] {
+    let nonce = solve(
+        128,
+        |n| {
+            let n = n.try_into().unwrap();
+            vec![commitment_of(k1, n) ^ commitment_of(k2, n)]
+        },
+        &[0],
+    );
+    nonce.try_into().unwrap()
+}
+
+const K1: [u8; 16] = *b"first candidate!";
+const K2: [u8; 16] = *b"second candidate";
+
+// A partitioning oracle needs ciphertexts that decrypt under many keys, with the commitment a
+// ciphertext only decrypts under the key it was committed to
+#[test]
+fn partitioning_oracle_test() {
+    let tag = Tag::from([0x42; 16]);
+    let nonce = [0x24; 16];
+    let ciphertext = multi_key_ciphertext(&K1, &K2, &nonce, &tag);
+    assert_ne!(commitment_of(&K1, &nonce), commitment_of(&K2, &nonce));
+
+    // Plain SUNDAE accepts the ciphertext under both keys
+    for key in [K1, K2] {
+        let mut buffer = ciphertext.clone();
+        Plain::new(&key.into())
+            .decrypt_in_place_detached(&nonce.into(), b"", &mut buffer, &tag)
+            .unwrap();
+    }
+
+    // The committing variant only under the key whose commitment is attached
+    for (committed, other) in [(K1, K2), (K2, K1)] {
+        let committed = Committing::new(&committed.into());
+        let other = Committing::new(&other.into());
+
+        let mut full_tag = committed
+            .encrypt_in_place_detached(&nonce.into(), b"", &mut [])
+            .unwrap();
+        full_tag[16..].copy_from_slice(&tag);
+
+        let mut buffer = ciphertext.clone();
+        committed
+            .decrypt_in_place_detached(&nonce.into(), b"", &mut buffer, &full_tag)
+            .unwrap();
+
+        let mut buffer = ciphertext.clone();
+        assert!(other
+            .decrypt_in_place_detached(&nonce.into(), b"", &mut buffer, &full_tag)
+            .is_err());
+        assert_eq!(buffer, ciphertext);
+    }
+}
+
+// The commitment is a CBC-MAC, as strong as the block cipher and no stronger: with the linear toy
+// cipher, the nonce under which two keys share the commitment is the solution of a linear system,
+// and the same ciphertext and full tag then decrypt under both keys
+#[test]
+fn commitment_collision_test() {
+    let nonce = colliding_nonce(&K1, &K2);
+    assert_eq!(commitment_of(&K1, &nonce), commitment_of(&K2, &nonce));
+
+    let tag = Tag::from([0x42; 16]);
+    let ciphertext = multi_key_ciphertext(&K1, &K2, &nonce, &tag);
+    let mut full_tag = Committing::new(&K1.into())
+        .encrypt_in_place_detached(&nonce.into(), b"", &mut [])
+        .unwrap();
+    full_tag[16..].copy_from_slice(&tag);
+
+    let mut plaintexts = Vec::new();
+    for key in [K1, K2] {
+        let mut buffer = ciphertext.clone();
+        Committing::new(&key.into())
+            .decrypt_in_place_detached(&nonce.into(), b"", &mut buffer, &full_tag)
+            .unwrap();
+        plaintexts.push(buffer);
+    }
+    assert_ne!(plaintexts[0], plaintexts[1]);
+}
+
+#[cfg(feature = "aes")]
+#[test]
+fn aes_test() {
+    use aes::Aes128;
+    use cipher::consts::U8;
+
+    let cipher: SundaeCommitting<Aes128, U8> = SundaeCommitting::new(b"just another key".into());
+    let other: SundaeCommitting<Aes128, U8> = SundaeCommitting::new(b"another key, too".into());
+    let nonce = b"thenonce".into();
+
+    let mut buffer = *b"plaintext message";
+    let tag = cipher
+        .encrypt_in_place_detached(nonce, b"ad", &mut buffer)
+        .unwrap();
+    let ciphertext = buffer;
+
+    // Same ciphertext and SUNDAE tag as the plain construction
+    let plain: Sundae<Aes128, U8> = Sundae::new(b"just another key".into());
+    let mut expected = *b"plaintext message";
+    let t = plain
+        .encrypt_in_place_detached(nonce, b"ad", &mut expected)
+        .unwrap();
+    assert_eq!(ciphertext, expected);
+    assert_eq!(tag[16..], t[..]);
+
+    assert!(other
+        .decrypt_in_place_detached(nonce, b"ad", &mut buffer, &tag)
+        .is_err());
+
+    let mut forged = tag;
+    forged[0] ^= 1;
+    assert!(cipher
+        .decrypt_in_place_detached(nonce, b"ad", &mut buffer, &forged)
+        .is_err());
+    assert_eq!(buffer, ciphertext);
+
+    cipher
+        .decrypt_in_place_detached(nonce, b"ad", &mut buffer, &tag)
+        .unwrap();
+    assert_eq!(&buffer, b"plaintext message");
+}
@@ -0,0 +1,88 @@
+#![cfg(feature = "aes")]
+
+use aes::Aes128;
+use cipher::consts::{U0, U12, U16, U8};
+use sundae::{
+    aead::{AeadInPlace, KeyInit},
+    DynError, Nonce, Sundae, SundaeDyn, SundaeNonceSize,
+};
+
+const KEY: &[u8; 16] = b"just another key";
+
+// Same output as the statically sized variant for the nonce length
+fn check<N: SundaeNonceSize>(cipher: &SundaeDyn<Aes128>) {
+    let fixed: Sundae<Aes128, N> = Sundae::new(KEY.into());
+    let nonce = Nonce::<N>::from_exact_iter((0..).take(N::USIZE)).unwrap();
+
+    let mut expected = *b"plaintext message";
+    let tag = fixed
+        .encrypt_in_place_detached(&nonce, b"ad", &mut expected)
+        .unwrap();
+
+    let mut buffer = *b"plaintext message";
+    let t = cipher
+        .encrypt_in_place_detached(&nonce, b"ad", &mut buffer)
+        .unwrap();
+    assert_eq!(t, tag);
+    assert_eq!(buffer, expected);
+
+    cipher
+        .decrypt_in_place_detached(&nonce, b"ad", &mut buffer, &tag)
+        .unwrap();
+    assert_eq!(&buffer, b"plaintext message");
+}
+
+#[test]
+fn nonce_sizes_test() {
+    let cipher = SundaeDyn::<Aes128>::new(KEY.into());
+    check::<U0>(&cipher);
+    check::<U8>(&cipher);
+    check::<U12>(&cipher);
+    check::<U16>(&cipher);
+}
+
+#[test]
+fn nonce_length_test() {
+    let cipher = SundaeDyn::<Aes128>::new(KEY.into());
+    let tag = Default::default();
+
+    for len in [1, 4, 7, 9, 15, 17, 32] {
+        let nonce = vec![0; len];
+        let mut buffer = *b"plaintext message";
+        assert_eq!(
+            cipher.encrypt_in_place_detached(&nonce, b"", &mut buffer),
+            Err(DynError::NonceLength(len))
+        );
+        assert_eq!(
+            cipher.decrypt_in_place_detached(&nonce, b"", &mut buffer, &tag),
+            Err(DynError::NonceLength(len))
+        );
+        assert_eq!(&buffer, b"plaintext message");
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn buffer_test() {
+    let cipher = SundaeDyn::<Aes128>::new(KEY.into());
+    let nonce = b"a 128-bit nonce!";
+
+    let mut buffer = b"plaintext message".to_vec();
+    cipher.encrypt_in_place(nonce, b"ad", &mut buffer).unwrap();
+    assert_eq!(buffer.len(), 17 + 16);
+
+    let ciphertext = buffer.clone();
+    assert_eq!(
+        cipher.decrypt_in_place(nonce, b"da", &mut buffer),
+        Err(DynError::Aead)
+    );
+    assert_eq!(buffer, ciphertext);
+
+    cipher.decrypt_in_place(nonce, b"ad", &mut buffer).unwrap();
+    assert_eq!(&buffer, b"plaintext message");
+
+    assert_eq!(
+        cipher.decrypt_in_place(nonce, b"ad", &mut vec![0; 15]),
+        Err(DynError::Aead)
+    );
+}
@@ -0,0 +1,153 @@
+#![cfg(all(feature = "aes", feature = "alloc"))]
+
+use camellia::Camellia128;
+use sundae::{
+    aead::{consts::U8, KeyInit},
+    envelope::{self, Envelope, EnvelopeError},
+    Sundae, SundaeAes, SundaeAes96,
+};
+
+const KEY: &[u8; 16] = b"just another key";
+
+#[test]
+fn roundtrip_test() {
+    let cipher = SundaeAes::new(KEY.into());
+    let sealed =
+        envelope::seal(&cipher, b"key 1", b"thenonce".into(), b"plaintext message").unwrap();
+    assert_eq!(sealed.len(), envelope::sealed_len::<U8>(5, 17));
+
+    let fields = Envelope::parse(&sealed).unwrap();
+    assert_eq!(&sealed[..5], b"SNDE\x01");
+    assert_eq!(fields.header, &sealed[..8 + 5 + 8]);
+    assert_eq!(fields.cipher_id, 0x01);
+    assert_eq!(fields.key_id, b"key 1");
+    assert_eq!(fields.nonce, b"thenonce");
+    assert_eq!(fields.ciphertext.len(), 17);
+
+    assert_eq!(
+        envelope::open(&cipher, &sealed).unwrap(),
+        b"plaintext message"
+    );
+
+    let mut buffer = sealed.clone();
+    let plaintext = envelope::open_in_place(&cipher, &mut buffer).unwrap();
+    assert_eq!(plaintext, b"plaintext message");
+
+    // Empty key ID and message
+    let sealed = envelope::seal(&cipher, b"", b"thenonce".into(), b"").unwrap();
+    assert_eq!(sealed.len(), 8 + 8 + 16);
+    assert_eq!(envelope::open(&cipher, &sealed).unwrap(), b"");
+}
+
+// The header is authenticated, changing any of its bytes fails
+#[test]
+fn header_test() {
+    let cipher = SundaeAes::new(KEY.into());
+    let sealed =
+        envelope::seal(&cipher, b"key 1", b"thenonce".into(), b"plaintext message").unwrap();
+
+    for i in 8..Envelope::parse(&sealed).unwrap().header.len() {
+        let mut forged = sealed.clone();
+        forged[i] ^= 1;
+        assert_eq!(envelope::open(&cipher, &forged), Err(EnvelopeError::Aead));
+
+        let mut buffer = forged.clone();
+        assert!(envelope::open_in_place(&cipher, &mut buffer).is_err());
+        assert_eq!(buffer, forged);
+    }
+
+    let mut forged = sealed.clone();
+    forged[0] = b'X';
+    assert_eq!(envelope::open(&cipher, &forged), Err(EnvelopeError::Magic));
+
+    let mut forged = sealed.clone();
+    forged[4] = 2;
+    assert_eq!(
+        envelope::open(&cipher, &forged),
+        Err(EnvelopeError::Version(2))
+    );
+
+    let mut forged = sealed.clone();
+    forged[7] = 200;
+    assert_eq!(
+        envelope::open(&cipher, &forged),
+        Err(EnvelopeError::Truncated)
+    );
+
+    for len in [0, 7, 8 + 5 + 8 + 15] {
+        assert_eq!(
+            Envelope::parse(&sealed[..len]),
+            Err(EnvelopeError::Truncated)
+        );
+    }
+}
+
+#[test]
+fn mismatch_test() {
+    let cipher = SundaeAes::new(KEY.into());
+    let sealed =
+        envelope::seal(&cipher, b"key 1", b"thenonce".into(), b"plaintext message").unwrap();
+
+    let other_nonce = SundaeAes96::new(KEY.into());
+    assert_eq!(
+        envelope::open(&other_nonce, &sealed),
+        Err(EnvelopeError::NonceLength(8))
+    );
+
+    #[cfg(feature = "gift")]
+    {
+        let other_cipher = sundae::SundaeGift64::new(KEY.into());
+        assert_eq!(
+            envelope::open(&other_cipher, &sealed),
+            Err(EnvelopeError::Cipher(0x01))
+        );
+    }
+}
+
+#[test]
+fn length_test() {
+    let cipher = SundaeAes::new(KEY.into());
+    let nonce = b"thenonce".into();
+
+    assert_eq!(
+        envelope::seal(&cipher, &[0; 256], nonce, b""),
+        Err(EnvelopeError::KeyIdLength(256))
+    );
+    assert!(envelope::seal(&cipher, &[0; 255], nonce, b"").is_ok());
+
+    let mut out = [0; 8 + 8 + 16 + 1];
+    assert_eq!(
+        envelope::seal_into(&cipher, b"", nonce, b"", &mut out),
+        Err(EnvelopeError::BufferLength)
+    );
+    envelope::seal_into(&cipher, b"", nonce, b"x", &mut out).unwrap();
+}
+
+// Block ciphers of other crates can not implement `CipherId`, they pass the ID instead
+#[test]
+fn explicit_id_test() {
+    const CAMELLIA: u8 = 0x80;
+    let cipher = Sundae::<Camellia128, U8>::new(KEY.into());
+    let nonce = b"thenonce".into();
+
+    let sealed =
+        envelope::seal_with_id(&cipher, CAMELLIA, b"key 1", nonce, b"plaintext message").unwrap();
+    assert_eq!(Envelope::parse(&sealed).unwrap().cipher_id, CAMELLIA);
+    assert_eq!(
+        envelope::open_with_id(&cipher, CAMELLIA, &sealed).unwrap(),
+        b"plaintext message"
+    );
+    assert_eq!(
+        envelope::open_with_id(&cipher, 0x81, &sealed),
+        Err(EnvelopeError::Cipher(CAMELLIA))
+    );
+    assert_eq!(
+        envelope::open(&SundaeAes::new(KEY.into()), &sealed),
+        Err(EnvelopeError::Cipher(CAMELLIA))
+    );
+
+    let mut out = vec![0; envelope::sealed_len::<U8>(0, 3)];
+    envelope::seal_into_with_id(&cipher, CAMELLIA, b"", nonce, b"abc", &mut out).unwrap();
+    let plaintext = envelope::open_in_place_with_id(&cipher, CAMELLIA, &mut out).unwrap();
+    assert_eq!(plaintext, b"abc");
+}
@@ -0,0 +1,40 @@
+#![cfg(feature = "heapless")]
+
+use camellia::Camellia128;
+use cipher::consts::U8;
+use sundae::{
+    aead::{heapless::Vec, AeadInPlace, KeyInit},
+    Nonce, Sundae,
+};
+
+#[test]
+fn heapless_test() {
+    let key = b"just another key";
+    let nonce = Nonce::from_slice(b"thenonce");
+    let ad = b"this will NOT be encrypted, but will be authenticated";
+
+    let cipher: Sundae<Camellia128, U8> = Sundae::new(key.into());
+
+    let mut buffer: Vec<u8, 128> = Vec::new();
+    buffer.extend_from_slice(b"this will be encrypted").unwrap();
+
+    cipher
+        .encrypt_in_place(nonce, ad, &mut buffer)
+        .expect("encryption failure!");
+    assert_ne!(&buffer[..22], b"this will be encrypted");
+
+    cipher
+        .decrypt_in_place(nonce, ad, &mut buffer)
+        .expect("decryption failure!");
+    assert_eq!(&buffer, b"this will be encrypted");
+
+    // A forged tag must leave the ciphertext untouched
+    cipher
+        .encrypt_in_place(nonce, ad, &mut buffer)
+        .expect("encryption failure!");
+    let ciphertext = buffer.clone();
+    let last = buffer.len() - 1;
+    buffer[last] ^= 1;
+    assert!(cipher.decrypt_in_place(nonce, ad, &mut buffer).is_err());
+    assert_eq!(&buffer[..last], &ciphertext[..last]);
+}
@@ -0,0 +1,95 @@
+#![cfg(feature = "aes")]
+
+use aes::Aes128;
+use cipher::consts::{U0, U16, U8};
+use sundae::{
+    aead::{AeadInPlace, KeyInit},
+    Nonce, Sundae, SundaeNonceSize,
+};
+
+// Chunked encryption and decryption agree with the one-shot API for every split
+fn check<N: SundaeNonceSize>() {
+    let cipher: Sundae<Aes128, N> = Sundae::new(b"just another key".into());
+    let nonce = Nonce::<N>::from_exact_iter((0..).take(N::USIZE)).unwrap();
+    let ad: Vec<u8> = (100..140).collect();
+    let pt: Vec<u8> = (0..70).collect();
+
+    for (ad_len, pt_len) in [(0, 0), (0, 5), (7, 0), (16, 16), (33, 47), (40, 70)] {
+        let (ad, pt) = (&ad[..ad_len], &pt[..pt_len]);
+        let mut ct = pt.to_vec();
+        let tag = cipher
+            .encrypt_in_place_detached(&nonce, ad, &mut ct)
+            .unwrap();
+
+        for chunk in [1, 5, 16, 17, 64] {
+            let mut encryptor = cipher.encryptor(&nonce);
+            for part in ad.chunks(chunk) {
+                encryptor.update_ad(part).unwrap();
+            }
+            for part in pt.chunks(chunk) {
+                encryptor.update_plaintext(part);
+            }
+            let (t, mut keystream) = encryptor.finalize();
+            assert_eq!(
+                t, tag,
+                "ad = {}, pt = {}, chunk = {}",
+                ad_len, pt_len, chunk
+            );
+
+            let mut buffer = pt.to_vec();
+            for part in buffer.chunks_mut(chunk) {
+                keystream.apply_keystream(part);
+            }
+            assert_eq!(
+                buffer, ct,
+                "ad = {}, pt = {}, chunk = {}",
+                ad_len, pt_len, chunk
+            );
+
+            let mut decryptor = cipher.decryptor(&nonce, &tag);
+            for part in ad.chunks(chunk) {
+                decryptor.update_ad(part).unwrap();
+            }
+            for part in buffer.chunks_mut(chunk) {
+                decryptor.decrypt(part);
+            }
+            decryptor.verify().unwrap();
+            assert_eq!(buffer, pt);
+        }
+    }
+}
+
+#[test]
+fn incremental_test() {
+    check::<U0>();
+    check::<U8>();
+    check::<U16>();
+}
+
+#[test]
+fn forgery_test() {
+    let cipher: Sundae<Aes128, U8> = Sundae::new(b"just another key".into());
+    let nonce = Nonce::from_slice(b"thenonce");
+
+    let mut encryptor = cipher.encryptor(nonce);
+    encryptor.update_plaintext(b"plaintext message");
+    let (tag, mut keystream) = encryptor.finalize();
+    let mut buffer = *b"plaintext message";
+    keystream.apply_keystream(&mut buffer);
+
+    let mut decryptor = cipher.decryptor(nonce, &tag);
+    buffer[0] ^= 1;
+    decryptor.decrypt(&mut buffer);
+    assert!(decryptor.verify().is_err());
+}
+
+#[test]
+fn ad_after_plaintext_test() {
+    let cipher: Sundae<Aes128, U8> = Sundae::new(b"just another key".into());
+    let nonce = Nonce::from_slice(b"thenonce");
+
+    let mut encryptor = cipher.encryptor(nonce);
+    encryptor.update_ad(b"ad").unwrap();
+    encryptor.update_plaintext(b"plaintext");
+    assert!(encryptor.update_ad(b"more ad").is_err());
+}
@@ -0,0 +1,84 @@
+#![cfg(all(feature = "aes", feature = "alloc"))]
+
+use aes::Aes128;
+use cipher::consts::U0;
+use sundae::{aead::KeyInit, keywrap, Sundae};
+
+#[test]
+fn roundtrip_test() {
+    let kek: Sundae<Aes128, U0> = Sundae::new(b"key encrypt key!".into());
+    let key: Vec<u8> = (0..32).collect();
+
+    let wrapped = keywrap::wrap_key(&kek, &key, b"header");
+    assert_eq!(wrapped.len(), 48);
+    // Deterministic
+    assert_eq!(wrapped, keywrap::wrap_key(&kek, &key, b"header"));
+    assert_ne!(wrapped, keywrap::wrap_key(&kek, &key, b"other header"));
+
+    assert_eq!(keywrap::unwrap_key(&kek, &wrapped, b"header").unwrap(), key);
+}
+
+#[test]
+fn reject_test() {
+    let kek: Sundae<Aes128, U0> = Sundae::new(b"key encrypt key!".into());
+    let other: Sundae<Aes128, U0> = Sundae::new(b"other kek key!!!".into());
+    let key = [0x42; 16];
+    let wrapped = keywrap::wrap_key(&kek, &key, b"header");
+
+    assert!(keywrap::unwrap_key(&kek, &wrapped, b"headex").is_err());
+    assert!(keywrap::unwrap_key(&other, &wrapped, b"header").is_err());
+    assert!(keywrap::unwrap_key(&kek, &wrapped[..15], b"header").is_err());
+
+    for i in 0..wrapped.len() {
+        let mut forged = wrapped.clone();
+        forged[i] ^= 1;
+        assert!(keywrap::unwrap_key(&kek, &forged, b"header").is_err());
+    }
+
+    // Failed unwrapping leaves no ciphertext or plaintext behind
+    let mut forged = wrapped.clone();
+    forged[0] ^= 1;
+    let mut out = [0xff; 16];
+    assert!(keywrap::unwrap_key_into(&kek, &forged, b"header", &mut out).is_err());
+    assert_eq!(out, [0; 16]);
+
+    // Output buffers of the wrong size
+    assert!(keywrap::wrap_key_into(&kek, &key, b"", &mut [0; 31]).is_err());
+    assert!(keywrap::unwrap_key_into(&kek, &wrapped, b"header", &mut [0; 15]).is_err());
+}
+
+// Wrapped keys are SUNDAE-GIFT-0 ciphertexts: the records of the KAT file with the key as PT and
+// the header as AD. The file is the one of the independent model, see tests/kat.rs, until the
+// official LWC_AEAD_KAT_128_0.txt is checked in.
+#[cfg(feature = "gift")]
+#[test]
+fn gift_test() {
+    use sundae::gift::Gift128;
+
+    let kat = include_str!("kats/sundae_gift_model_128_0.txt");
+    let decode = |s: &str| -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    };
+
+    let mut tested = 0;
+    for record in kat.split("\n\n").filter(|r| !r.trim().is_empty()) {
+        let field = |name: &str| {
+            record
+                .lines()
+                .filter_map(|line| line.split_once('='))
+                .find(|(n, _)| n.trim() == name)
+                .map(|(_, value)| decode(value.trim()))
+                .unwrap()
+        };
+        let kek: Sundae<Gift128, U0> = Sundae::new_from_slice(&field("Key")).unwrap();
+        let (key, header, expected) = (field("PT"), field("AD"), field("CT"));
+
+        assert_eq!(keywrap::wrap_key(&kek, &key, &header), expected);
+        assert_eq!(keywrap::unwrap_key(&kek, &expected, &header).unwrap(), key);
+        tested += 1;
+    }
+    assert_eq!(tested, 33 * 33);
+}
@@ -0,0 +1,101 @@
+#![cfg(feature = "aes")]
+
+use aes::Aes128;
+use cipher::consts::U0;
+use sundae::{
+    aead::{AeadInPlace, KeyInit},
+    digest::Mac,
+    Sundae, SundaeMac,
+};
+
+// The MAC agrees with nonce-less SUNDAE over associated data only, however the data is split
+#[test]
+fn incremental_test() {
+    let key = b"just another key";
+    let cipher: Sundae<Aes128, U0> = Sundae::new(key.into());
+    let data: Vec<u8> = (0..100).collect();
+
+    for len in 0..data.len() {
+        let expected = cipher
+            .encrypt_in_place_detached(&Default::default(), &data[..len], &mut [])
+            .unwrap();
+
+        for chunk in [1, 3, 15, 16, 17, 32, 100] {
+            let mut mac: SundaeMac<Aes128> = Mac::new(key.into());
+            for part in data[..len].chunks(chunk) {
+                mac.update(part);
+                mac.update(b"");
+            }
+            assert_eq!(
+                mac.finalize().into_bytes(),
+                expected,
+                "len = {}, chunk = {}",
+                len,
+                chunk
+            );
+        }
+    }
+}
+
+#[test]
+fn verify_test() {
+    let key = b"just another key";
+    let mut mac: SundaeMac<Aes128> = Mac::new(key.into());
+    mac.update(b"firmware image");
+    let tag = mac.clone().finalize().into_bytes();
+
+    mac.clone().verify_slice(&tag).unwrap();
+    mac.clone().verify_truncated_left(&tag[..8]).unwrap();
+
+    let mut forged = tag;
+    forged[15] ^= 1;
+    assert!(mac.clone().verify_slice(&forged).is_err());
+    assert!(mac.verify_slice(&tag[..15]).is_err());
+}
+
+#[test]
+fn reset_test() {
+    let key = b"just another key";
+    let mut mac: SundaeMac<Aes128> = Mac::new(key.into());
+
+    mac.update(b"first message");
+    let first = mac.finalize_reset().into_bytes();
+
+    mac.update(b"second message");
+    let second = mac.finalize_reset().into_bytes();
+
+    mac.update(b"first message");
+    assert_eq!(mac.finalize_reset().into_bytes(), first);
+    assert_ne!(first, second);
+
+    mac.update(b"discarded");
+    mac.reset();
+    mac.update(b"second message");
+    assert_eq!(mac.finalize().into_bytes(), second);
+}
+
+// Tags of the records without plaintext in the SUNDAE-GIFT-0 KAT file of the independent model
+// (tests/kats/sundae_gift_model_128_0.txt), not of the official NIST LWC known-answer tests
+#[cfg(feature = "gift")]
+#[test]
+fn gift_test() {
+    use sundae::gift::Gift128;
+
+    let key: Vec<u8> = (0..16).collect();
+    let vectors = [
+        (0, "de50f41fbefef36d5f3702fefeace6be"),
+        (8, "4b2e4ba1bfd51ba959303a5f6126f756"),
+        (17, "498856ca68b1e28f4a7f33049d57312f"),
+    ];
+
+    for (len, tag) in vectors {
+        let data: Vec<u8> = (0..len as u8).collect();
+        let mut mac: SundaeMac<Gift128> = Mac::new_from_slice(&key).unwrap();
+        mac.update(&data);
+        let tag: Vec<u8> = (0..32)
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&tag[i..i + 2], 16).unwrap())
+            .collect();
+        mac.verify_slice(&tag).unwrap();
+    }
+}
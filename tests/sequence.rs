@@ -0,0 +1,181 @@
+use sundae::{
+    aead::consts::{U12, U8},
+    MemoryStore, NonceSequence, NonceStore, SequenceError,
+};
+
+#[test]
+fn counter_test() {
+    let mut sequence = NonceSequence::<U12, _>::new(MemoryStore::default()).unwrap();
+    for i in 0..300u32 {
+        let nonce = sequence.next_nonce().unwrap();
+        assert_eq!(nonce[..8], [0; 8]);
+        assert_eq!(nonce[8..], i.to_be_bytes());
+        assert_eq!(sequence.store().mark(), u128::from(i) + 1);
+    }
+}
+
+// A restart resumes after the reserved window, never below a counter handed out before
+#[test]
+fn restart_test() {
+    let mut sequence = NonceSequence::<U8, _>::with_window(MemoryStore::default(), 10).unwrap();
+    for _ in 0..13 {
+        sequence.next_nonce().unwrap();
+    }
+    let store = sequence.into_store();
+    assert_eq!(store.mark(), 20);
+
+    let mut sequence = NonceSequence::<U8, _>::with_window(store, 10).unwrap();
+    assert_eq!(
+        sequence.next_nonce().unwrap().as_slice(),
+        20u64.to_be_bytes()
+    );
+    assert_eq!(sequence.store().mark(), 30);
+}
+
+#[test]
+fn exhaustion_test() {
+    let store = MemoryStore::new(u64::MAX as u128 - 1);
+    let mut sequence = NonceSequence::<U8, _>::with_window(store, 10).unwrap();
+    assert_eq!(sequence.remaining(), 2);
+
+    assert_eq!(
+        sequence.next_nonce().unwrap().as_slice(),
+        (u64::MAX - 1).to_be_bytes()
+    );
+    assert_eq!(sequence.next_nonce().unwrap().as_slice(), [0xff; 8]);
+    assert_eq!(sequence.next_nonce(), Err(SequenceError::Exhausted));
+    assert_eq!(sequence.remaining(), 0);
+    assert_eq!(sequence.store().mark(), u64::MAX as u128 + 1);
+
+    // Also after a restart
+    let mut sequence = NonceSequence::<U8, _>::new(sequence.into_store()).unwrap();
+    assert_eq!(sequence.next_nonce(), Err(SequenceError::Exhausted));
+}
+
+// Store refusing writes after a number of them
+struct FailingStore {
+    mark: u128,
+    writes: usize,
+}
+
+impl NonceStore for FailingStore {
+    type Error = &'static str;
+
+    fn load(&mut self) -> Result<u128, Self::Error> {
+        Ok(self.mark)
+    }
+
+    fn store(&mut self, mark: u128) -> Result<(), Self::Error> {
+        if self.writes == 0 {
+            return Err("flash worn out");
+        }
+        self.writes -= 1;
+        self.mark = mark;
+        Ok(())
+    }
+}
+
+// No nonce is handed out without its mark stored first
+#[test]
+fn store_failure_test() {
+    let store = FailingStore { mark: 0, writes: 1 };
+    let mut sequence = NonceSequence::<U8, _>::with_window(store, 2).unwrap();
+    sequence.next_nonce().unwrap();
+    sequence.next_nonce().unwrap();
+    assert_eq!(
+        sequence.next_nonce(),
+        Err(SequenceError::Store("flash worn out"))
+    );
+    assert_eq!(
+        sequence.next_nonce(),
+        Err(SequenceError::Store("flash worn out"))
+    );
+    assert_eq!(sequence.store().mark, 2);
+}
+
+#[cfg(all(feature = "aes", feature = "alloc"))]
+#[test]
+fn sealer_test() {
+    use sundae::{
+        aead::{AeadInPlace, KeyInit},
+        aes::Aes128,
+        SundaeAes96, SundaeSealer,
+    };
+
+    let key = b"just another key";
+    let mut sealer =
+        SundaeSealer::<Aes128, U12, _>::new(key.into(), MemoryStore::default()).unwrap();
+    let receiver = SundaeAes96::new(key.into());
+
+    let mut nonces = Vec::new();
+    for _ in 0..3 {
+        let mut buffer = *b"plaintext message";
+        let (nonce, tag) = sealer.seal_in_place_detached(b"ad", &mut buffer).unwrap();
+        receiver
+            .decrypt_in_place_detached(&nonce, b"ad", &mut buffer, &tag)
+            .unwrap();
+        assert_eq!(&buffer, b"plaintext message");
+        nonces.push(nonce);
+    }
+    assert!(nonces.windows(2).all(|w| w[0] < w[1]));
+
+    let mut buffer = b"plaintext message".to_vec();
+    let nonce = sealer.seal_in_place(b"ad", &mut buffer).unwrap();
+    assert_eq!(nonce[11], 3);
+    receiver
+        .decrypt_in_place(&nonce, b"ad", &mut buffer)
+        .unwrap();
+    assert_eq!(buffer, b"plaintext message");
+    assert_eq!(sealer.sequence().remaining(), (1 << 96) - 4);
+}
+
+// A buffer without room for the tag fails before a nonce is used up
+#[cfg(all(feature = "aes", feature = "alloc"))]
+#[test]
+fn sealer_capacity_test() {
+    use sundae::{
+        aead::{self, Buffer},
+        aes::Aes128,
+        SundaeSealer,
+    };
+
+    // Buffer that can not grow
+    struct Full(Vec<u8>);
+
+    impl AsRef<[u8]> for Full {
+        fn as_ref(&self) -> &[u8] {
+            &self.0
+        }
+    }
+
+    impl AsMut<[u8]> for Full {
+        fn as_mut(&mut self) -> &mut [u8] {
+            &mut self.0
+        }
+    }
+
+    impl Buffer for Full {
+        fn extend_from_slice(&mut self, _other: &[u8]) -> aead::Result<()> {
+            Err(aead::Error)
+        }
+
+        fn truncate(&mut self, len: usize) {
+            self.0.truncate(len);
+        }
+    }
+
+    let mut sealer =
+        SundaeSealer::<Aes128, U12, _>::new(b"just another key".into(), MemoryStore::default())
+            .unwrap();
+    let mut buffer = Full(b"plaintext message".to_vec());
+    assert_eq!(
+        sealer.seal_in_place(b"ad", &mut buffer),
+        Err(SequenceError::Aead)
+    );
+    assert_eq!(buffer.0, b"plaintext message");
+    assert_eq!(sealer.sequence().remaining(), 1 << 96);
+
+    let mut buffer = b"plaintext message".to_vec();
+    let nonce = sealer.seal_in_place(b"ad", &mut buffer).unwrap();
+    assert_eq!(nonce[11], 0);
+}
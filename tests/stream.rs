@@ -0,0 +1,103 @@
+#![cfg(all(feature = "aes", feature = "alloc", feature = "stream"))]
+
+use sundae::{
+    aead::{
+        stream::{
+            DecryptorBE32, DecryptorLE31, EncryptorBE32, EncryptorLE31, NewStream, StreamBE32,
+            StreamLE31, StreamPrimitive,
+        },
+        KeyInit,
+    },
+    SundaeAes128, SundaeAes96,
+};
+
+const KEY: &[u8; 16] = b"just another key";
+const SEGMENTS: [&[u8]; 4] = [b"first", b"second segment", b"", b"the last segment"];
+
+#[test]
+fn be32_test() {
+    let cipher = SundaeAes96::new(KEY.into());
+    let nonce = b"7 bytes".into();
+
+    let mut encryptor = EncryptorBE32::from_aead(cipher.clone(), nonce);
+    let mut ciphertexts: Vec<Vec<u8>> = SEGMENTS[..3]
+        .iter()
+        .map(|s| encryptor.encrypt_next(*s).unwrap())
+        .collect();
+    ciphertexts.push(encryptor.encrypt_last(SEGMENTS[3]).unwrap());
+
+    let mut decryptor = DecryptorBE32::from_aead(cipher, nonce);
+    for (c, s) in ciphertexts[..3].iter().zip(SEGMENTS) {
+        assert_eq!(decryptor.decrypt_next(c.as_slice()).unwrap(), s);
+    }
+    assert_eq!(
+        decryptor.decrypt_last(ciphertexts[3].as_slice()).unwrap(),
+        SEGMENTS[3]
+    );
+}
+
+#[test]
+fn le31_test() {
+    let cipher = SundaeAes128::new(KEY.into());
+    let nonce = b"twelve bytes".into();
+
+    let mut encryptor = EncryptorLE31::from_aead(cipher.clone(), nonce);
+    let first = encryptor.encrypt_next(SEGMENTS[0]).unwrap();
+    let last = encryptor.encrypt_last(SEGMENTS[1]).unwrap();
+
+    let mut decryptor = DecryptorLE31::from_aead(cipher, nonce);
+    assert_eq!(
+        decryptor.decrypt_next(first.as_slice()).unwrap(),
+        SEGMENTS[0]
+    );
+    assert_eq!(
+        decryptor.decrypt_last(last.as_slice()).unwrap(),
+        SEGMENTS[1]
+    );
+}
+
+// The last segment only decrypts as the last one, and only the last segment does
+#[test]
+fn last_block_flag_test() {
+    let stream = StreamBE32::from_aead(SundaeAes96::new(KEY.into()), b"7 bytes".into());
+
+    let next = stream.encrypt(0, false, b"segment".as_ref()).unwrap();
+    let last = stream.encrypt(0, true, b"segment".as_ref()).unwrap();
+    assert_ne!(next, last);
+
+    assert!(stream.decrypt(0, true, next.as_slice()).is_err());
+    assert!(stream.decrypt(0, false, last.as_slice()).is_err());
+    assert!(stream.decrypt(0, false, next.as_slice()).is_ok());
+    assert!(stream.decrypt(0, true, last.as_slice()).is_ok());
+}
+
+#[test]
+fn reordering_test() {
+    let stream = StreamLE31::from_aead(SundaeAes96::new(KEY.into()), b"8 bytes!".into());
+
+    let first = stream.encrypt(0, false, SEGMENTS[0]).unwrap();
+    let second = stream.encrypt(1, false, SEGMENTS[1]).unwrap();
+
+    assert!(stream.decrypt(0, false, second.as_slice()).is_err());
+    assert!(stream.decrypt(1, false, first.as_slice()).is_err());
+    assert_eq!(
+        stream.decrypt(1, false, second.as_slice()).unwrap(),
+        SEGMENTS[1]
+    );
+}
+
+// Dropping the final segment leaves a stream whose new last segment fails to decrypt as last
+#[test]
+fn truncation_test() {
+    let cipher = SundaeAes96::new(KEY.into());
+    let nonce = b"7 bytes".into();
+
+    let mut encryptor = EncryptorBE32::from_aead(cipher.clone(), nonce);
+    let first = encryptor.encrypt_next(SEGMENTS[0]).unwrap();
+    let second = encryptor.encrypt_next(SEGMENTS[1]).unwrap();
+    let _last = encryptor.encrypt_last(SEGMENTS[3]).unwrap();
+
+    let mut decryptor = DecryptorBE32::from_aead(cipher, nonce);
+    decryptor.decrypt_next(first.as_slice()).unwrap();
+    assert!(decryptor.decrypt_last(second.as_slice()).is_err());
+}
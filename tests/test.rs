@@ -1,3 +1,5 @@
+#![cfg(all(feature = "alloc", feature = "aes"))]
+
 use aead::Payload;
 use camellia::Camellia128;
 use cipher::consts::U8;
@@ -138,7 +140,6 @@ fn extensive_test() {
 }
 
 #[test]
-#[should_panic]
 fn tag_test() {
     let key = [0u8; 16];
     let nonce = [0u8; 8];
@@ -153,5 +154,5 @@ fn tag_test() {
 
     println!("{:#02x?}", c);
 
-    cipher.decrypt(&nonce.into(), c.as_ref()).expect("");
+    assert!(cipher.decrypt(&nonce.into(), c.as_ref()).is_err());
 }
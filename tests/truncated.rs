@@ -0,0 +1,100 @@
+#![cfg(feature = "aes")]
+
+use std::collections::HashMap;
+use sundae::{
+    aead::{
+        consts::{U16, U4, U8},
+        AeadInPlace, KeyInit,
+    },
+    aes::Aes128,
+    SundaeAes, SundaeTagSize, SundaeTrunc,
+};
+
+const KEY: &[u8; 16] = b"just another key";
+
+fn check<T: SundaeTagSize<U8>>() {
+    let cipher = SundaeTrunc::<Aes128, U8, T>::new(KEY.into());
+    let nonce = b"thenonce".into();
+
+    for len in [0, 1, 15, 16, 17, 33] {
+        let plaintext: Vec<u8> = (0..len as u8).collect();
+        let mut buffer = plaintext.clone();
+        let tag = cipher
+            .encrypt_in_place_detached(nonce, b"ad", &mut buffer)
+            .unwrap();
+        assert_eq!(tag.len(), T::USIZE);
+
+        // The tag is a prefix of the full SUNDAE tag
+        let mut full = plaintext.clone();
+        let full_tag = SundaeAes::new(KEY.into())
+            .encrypt_in_place_detached(nonce, b"ad", &mut full)
+            .unwrap();
+        assert_eq!(tag[..], full_tag[..T::USIZE]);
+
+        let ciphertext = buffer.clone();
+        for i in 0..tag.len() {
+            let mut forged = tag.clone();
+            forged[i] ^= 1;
+            assert!(cipher
+                .decrypt_in_place_detached(nonce, b"ad", &mut buffer, &forged)
+                .is_err());
+            assert_eq!(buffer, ciphertext);
+        }
+        assert!(cipher
+            .decrypt_in_place_detached(nonce, b"da", &mut buffer, &tag)
+            .is_err());
+        assert_eq!(buffer, ciphertext);
+
+        cipher
+            .decrypt_in_place_detached(nonce, b"ad", &mut buffer, &tag)
+            .unwrap();
+        assert_eq!(buffer, plaintext);
+    }
+}
+
+#[test]
+fn tag_size_test() {
+    check::<U4>();
+    check::<U8>();
+    check::<U16>();
+}
+
+// Full-length tags give plain SUNDAE
+#[test]
+fn full_tag_test() {
+    let cipher = SundaeTrunc::<Aes128, U8, U16>::new(KEY.into());
+    let nonce = b"thenonce".into();
+
+    let mut buffer = *b"plaintext message";
+    let tag = cipher
+        .encrypt_in_place_detached(nonce, b"ad", &mut buffer)
+        .unwrap();
+
+    let mut expected = *b"plaintext message";
+    let expected_tag = SundaeAes::new(KEY.into())
+        .encrypt_in_place_detached(nonce, b"ad", &mut expected)
+        .unwrap();
+    assert_eq!(buffer, expected);
+    assert_eq!(tag, expected_tag);
+}
+
+// Messages sharing a short tag under different nonces still get different keystreams
+#[test]
+fn tag_collision_test() {
+    let cipher = SundaeTrunc::<Aes128, U8, U4>::new(KEY.into());
+    let mut seen = HashMap::new();
+
+    // A collision of 4-byte tags is expected after about 2^16 messages
+    for i in 0u64..1 << 20 {
+        let mut keystream = [0; 32];
+        let tag = cipher
+            .encrypt_in_place_detached(&i.to_be_bytes().into(), b"", &mut keystream)
+            .unwrap();
+
+        if let Some(other) = seen.insert(tag, keystream) {
+            assert_ne!(other, keystream);
+            return;
+        }
+    }
+    panic!("no tag collision found");
+}
@@ -0,0 +1,88 @@
+#![cfg(feature = "aes")]
+
+use sundae::{
+    aead::{AeadInPlace, KeyInit},
+    SundaeAes, SundaeAes0,
+};
+
+const KEY: &[u8; 16] = b"just another key";
+
+#[test]
+fn roundtrip_test() {
+    let cipher = SundaeAes::new(KEY.into());
+    let nonce = b"thenonce".into();
+    let ad: [&[u8]; 3] = [b"header", b"routing info", b"v2"];
+
+    let mut buffer = *b"plaintext message";
+    let tag = cipher.encrypt_with_ad_vector(nonce, &ad, &mut buffer);
+    assert_ne!(&buffer, b"plaintext message");
+
+    let ciphertext = buffer;
+    for i in 0..ad.len() {
+        let mut forged = ad;
+        forged[i] = b"forged";
+        assert!(cipher
+            .decrypt_with_ad_vector(nonce, &forged, &mut buffer, &tag)
+            .is_err());
+        assert_eq!(buffer, ciphertext);
+    }
+    assert!(cipher
+        .decrypt_with_ad_vector(nonce, &ad[..2], &mut buffer, &tag)
+        .is_err());
+
+    cipher
+        .decrypt_with_ad_vector(nonce, &ad, &mut buffer, &tag)
+        .unwrap();
+    assert_eq!(&buffer, b"plaintext message");
+}
+
+// Splitting the same bytes into different components changes the tag
+#[test]
+fn unambiguous_test() {
+    let cipher = SundaeAes0::new(KEY.into());
+    let nonce = Default::default();
+    let long = [0x42; 40];
+
+    let splits: [&[&[u8]]; 10] = [
+        &[],
+        &[b""],
+        &[b"", b""],
+        &[b"ab", b"c"],
+        &[b"a", b"bc"],
+        &[b"abc"],
+        &[b"abc", b""],
+        &[&long],
+        &[&long[..16], &long[16..]],
+        &[&long[..32], &long[32..]],
+    ];
+
+    let tags: Vec<_> = splits
+        .iter()
+        .map(|ad| cipher.encrypt_with_ad_vector(&nonce, ad, &mut [1, 2, 3]))
+        .collect();
+    for i in 0..tags.len() {
+        for j in i + 1..tags.len() {
+            assert_ne!(tags[i], tags[j], "{:?} and {:?}", splits[i], splits[j]);
+        }
+    }
+}
+
+// Vector associated data is domain-separated from the regular API
+#[test]
+fn domain_separation_test() {
+    let nonce = b"thenonce".into();
+    let cipher = SundaeAes::new(KEY.into());
+
+    let regular = cipher
+        .encrypt_in_place_detached(nonce, b"header", &mut [])
+        .unwrap();
+    let vector = cipher.encrypt_with_ad_vector(nonce, &[b"header"], &mut []);
+    assert_ne!(regular, vector);
+
+    let cipher = SundaeAes0::new(KEY.into());
+    let regular = cipher
+        .encrypt_in_place_detached(&Default::default(), b"", &mut [])
+        .unwrap();
+    let vector = cipher.encrypt_with_ad_vector(&Default::default(), &[], &mut []);
+    assert_ne!(regular, vector);
+}
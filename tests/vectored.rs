@@ -0,0 +1,102 @@
+#![cfg(feature = "aes")]
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::io::IoSliceMut;
+use sundae::{
+    aead::{AeadInPlace, KeyInit},
+    SundaeAes,
+};
+
+const KEY: &[u8; 16] = b"just another key";
+
+// Split `buffer` at the given ascending positions
+fn split<'a>(mut buffer: &'a mut [u8], cuts: &[usize]) -> Vec<&'a mut [u8]> {
+    let mut segments = Vec::new();
+    let mut pos = 0;
+    for &cut in cuts {
+        let (segment, rest) = buffer.split_at_mut(cut - pos);
+        segments.push(segment);
+        buffer = rest;
+        pos = cut;
+    }
+    segments.push(buffer);
+    segments
+}
+
+// Any segmentation, including empty segments and blocks straddling several segments, matches
+// contiguous encryption
+#[test]
+fn contiguous_test() {
+    let cipher = SundaeAes::new(KEY.into());
+    let nonce = b"thenonce".into();
+    let mut rng = StdRng::seed_from_u64(42);
+
+    for len in [0, 1, 15, 16, 17, 31, 32, 33, 100] {
+        let plaintext: Vec<u8> = (0..len as u8).collect();
+        for ad in [&b""[..], b"ad"] {
+            let mut expected = plaintext.clone();
+            let expected_tag = cipher
+                .encrypt_in_place_detached(nonce, ad, &mut expected)
+                .unwrap();
+
+            for _ in 0..20 {
+                let mut cuts: Vec<usize> = (0..rng.gen_range(0..6))
+                    .map(|_| rng.gen_range(0..=len))
+                    .collect();
+                cuts.sort_unstable();
+
+                let mut buffer = plaintext.clone();
+                let tag = cipher.encrypt_vectored(nonce, ad, &mut split(&mut buffer, &cuts));
+                assert_eq!(buffer, expected);
+                assert_eq!(tag, expected_tag);
+
+                cipher
+                    .decrypt_vectored(nonce, ad, &mut split(&mut buffer, &cuts), &tag)
+                    .unwrap();
+                assert_eq!(buffer, plaintext);
+            }
+        }
+    }
+}
+
+#[test]
+fn reject_test() {
+    let cipher = SundaeAes::new(KEY.into());
+    let nonce = b"thenonce".into();
+
+    let mut buffer = *b"a plaintext message spanning several blocks";
+    let tag = cipher.encrypt_vectored(nonce, b"ad", &mut split(&mut buffer, &[5, 20]));
+    let ciphertext = buffer;
+
+    let mut forged = tag;
+    forged[0] ^= 1;
+    assert!(cipher
+        .decrypt_vectored(nonce, b"ad", &mut split(&mut buffer, &[7]), &forged)
+        .is_err());
+    assert_eq!(buffer, ciphertext);
+
+    assert!(cipher
+        .decrypt_vectored(nonce, b"da", &mut split(&mut buffer, &[7]), &tag)
+        .is_err());
+    assert_eq!(buffer, ciphertext);
+}
+
+#[test]
+fn io_slice_test() {
+    let cipher = SundaeAes::new(KEY.into());
+    let nonce = b"thenonce".into();
+
+    let mut expected = *b"plaintext message";
+    let expected_tag = cipher
+        .encrypt_in_place_detached(nonce, b"", &mut expected)
+        .unwrap();
+
+    let (mut head, mut tail) = (*b"plaintext", *b" message");
+    let tag = cipher.encrypt_vectored(
+        nonce,
+        b"",
+        &mut [IoSliceMut::new(&mut head), IoSliceMut::new(&mut tail)],
+    );
+    assert_eq!(tag, expected_tag);
+    assert_eq!([&head[..], &tail[..]].concat(), expected);
+}
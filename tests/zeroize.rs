@@ -0,0 +1,51 @@
+#![cfg(feature = "zeroize")]
+
+use sundae::zeroize::ZeroizeOnDrop;
+
+fn assert_zeroize_on_drop<T: ZeroizeOnDrop>() {}
+
+// The `zeroize` feature turns on the one of the `aes` crate
+#[cfg(feature = "aes")]
+#[test]
+fn aes_zeroize_on_drop_test() {
+    assert_zeroize_on_drop::<sundae::aes::Aes128>();
+    assert_zeroize_on_drop::<sundae::SundaeAes>();
+    assert_zeroize_on_drop::<sundae::SundaeAes128>();
+}
+
+#[cfg(feature = "gift")]
+#[test]
+fn zeroize_test() {
+    use sundae::{
+        aead::{AeadInPlace, KeyInit},
+        gift::Gift128,
+        zeroize::Zeroize,
+        SundaeGift64,
+    };
+
+    assert_zeroize_on_drop::<Gift128>();
+    assert_zeroize_on_drop::<SundaeGift64>();
+
+    let nonce = Default::default();
+    let mut cipher = SundaeGift64::new(b"just another key".into());
+    let mut zeroized = SundaeGift64::new(&[0u8; 16].into());
+    zeroized.zeroize();
+
+    let mut m1 = *b"plaintext message";
+    let mut m2 = m1;
+    let t1 = cipher
+        .encrypt_in_place_detached(&nonce, b"", &mut m1)
+        .unwrap();
+    let t2 = zeroized
+        .encrypt_in_place_detached(&nonce, b"", &mut m2)
+        .unwrap();
+    assert_ne!((t1, m1), (t2, m2));
+
+    // A wiped key schedule no longer depends on the key
+    cipher.zeroize();
+    let mut m1 = *b"plaintext message";
+    let t1 = cipher
+        .encrypt_in_place_detached(&nonce, b"", &mut m1)
+        .unwrap();
+    assert_eq!((t1, m1), (t2, m2));
+}